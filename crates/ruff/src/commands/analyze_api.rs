@@ -1,15 +1,19 @@
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use log::{debug, info, trace};
 use rayon::prelude::*;
+use ruff_linter::api::analyze_package_api_with_parallelism;
+use ruff_linter::package::PackageRoot;
+use ruff_linter::strict::{analyze_package_strict, DiagnosticKind};
 use ruff_python_ast as ast;
 use ruff_python_ast::ExprContext;
 use ruff_workspace::resolver::{python_files_in_path, ResolvedFile, Resolver};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 
 use crate::args::{AnalyzeApiArgs, ConfigArguments};
@@ -20,12 +24,145 @@ use crate::{resolve_default_files, ExitStatus};
 // Type aliases for complex types
 //------------------------------------------------------------------------------
 
-/// Maps a symbol name to its usage count and importing files
-type SymbolUsageMap = HashMap<String, (usize, HashSet<PathBuf>)>;
+/// A module's dotted qualified name, as produced by [`get_module_name_from_path`].
+type ModuleName = String;
+
+/// Maps a symbol name to the usage it accumulated across external files.
+type SymbolUsageMap = HashMap<String, UsageTally>;
 
 /// A collection of Python files with their resolved information
 type ResolvedFileCollection = Vec<(PathBuf, ResolvedFile)>;
 
+//------------------------------------------------------------------------------
+// Consumer-role classification
+//------------------------------------------------------------------------------
+
+/// The role an external consumer plays, inferred from its path using Cargo's
+/// directory conventions (`tests/`, `examples/`, `benches/`). Everything that is
+/// not one of those is treated as production library/application code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ConsumerRole {
+    /// Real downstream code — the usage that makes a symbol genuinely public.
+    Production,
+    /// A project's own test suite.
+    Test,
+    /// Example or sample programs.
+    Example,
+    /// Benchmarks.
+    Bench,
+}
+
+impl ConsumerRole {
+    /// The roles in the order they are rendered in breakdowns.
+    const ORDER: [ConsumerRole; 4] = [
+        ConsumerRole::Production,
+        ConsumerRole::Test,
+        ConsumerRole::Example,
+        ConsumerRole::Bench,
+    ];
+}
+
+impl std::fmt::Display for ConsumerRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Production => write!(f, "production"),
+            Self::Test => write!(f, "test"),
+            Self::Example => write!(f, "example"),
+            Self::Bench => write!(f, "bench"),
+        }
+    }
+}
+
+/// Classifies an external file into a [`ConsumerRole`] by matching directory
+/// components (and the `test_*` / `*_test.py` filename conventions) against a
+/// configurable set of role directory names.
+pub(crate) struct RoleClassifier {
+    test_dirs: Vec<String>,
+    example_dirs: Vec<String>,
+    bench_dirs: Vec<String>,
+}
+
+impl RoleClassifier {
+    /// Build a classifier, falling back to Cargo's default directory names for
+    /// any role the project did not override.
+    fn new(test_dirs: &[String], example_dirs: &[String], bench_dirs: &[String]) -> Self {
+        fn or_default(configured: &[String], defaults: &[&str]) -> Vec<String> {
+            if configured.is_empty() {
+                defaults.iter().map(|s| s.to_string()).collect()
+            } else {
+                configured.to_vec()
+            }
+        }
+
+        Self {
+            test_dirs: or_default(test_dirs, &["tests", "test"]),
+            example_dirs: or_default(example_dirs, &["examples"]),
+            bench_dirs: or_default(bench_dirs, &["benches"]),
+        }
+    }
+
+    /// Classify a file path by its role. A directory component match wins; a
+    /// bare module whose filename follows the `test_*` / `*_test` convention is
+    /// classified as a test even outside a test directory.
+    fn classify(&self, path: &Path) -> ConsumerRole {
+        let has_component = |names: &[String]| {
+            path.components().any(|component| {
+                component
+                    .as_os_str()
+                    .to_str()
+                    .map_or(false, |name| names.iter().any(|dir| dir == name))
+            })
+        };
+
+        if has_component(&self.test_dirs) {
+            return ConsumerRole::Test;
+        }
+        if has_component(&self.example_dirs) {
+            return ConsumerRole::Example;
+        }
+        if has_component(&self.bench_dirs) {
+            return ConsumerRole::Bench;
+        }
+
+        if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+            if stem.starts_with("test_") || stem.ends_with("_test") {
+                return ConsumerRole::Test;
+            }
+        }
+
+        ConsumerRole::Production
+    }
+}
+
+/// The usage a single symbol accumulated: a total count, the set of files that
+/// imported it, and a per-[`ConsumerRole`] breakdown of those usages.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct UsageTally {
+    count: usize,
+    importers: HashSet<PathBuf>,
+    by_role: std::collections::BTreeMap<ConsumerRole, usize>,
+}
+
+impl UsageTally {
+    /// Record one usage from a file playing `role`.
+    fn record(&mut self, file_path: &Path, role: ConsumerRole) {
+        self.count += 1;
+        self.importers.insert(file_path.to_path_buf());
+        *self.by_role.entry(role).or_insert(0) += 1;
+    }
+
+    /// Fold another tally into this one, summing counts and role breakdowns and
+    /// unioning the importer sets.
+    fn merge(&mut self, other: UsageTally) {
+        self.count += other.count;
+        self.importers.extend(other.importers);
+        for (role, count) in other.by_role {
+            *self.by_role.entry(role).or_insert(0) += count;
+        }
+    }
+}
+
 //------------------------------------------------------------------------------
 // Analysis context structs
 //------------------------------------------------------------------------------
@@ -88,11 +225,19 @@ pub(crate) struct ApiAnalyzer {
     /// Candidate symbols to check for usage
     candidates: HashMap<String, DefinedSymbol>,
 
-    /// Usage counts for candidate symbols
-    usage_counts: Arc<Mutex<SymbolUsageMap>>,
-
     /// Name of the target module being analyzed
     target_module_name: String,
+
+    /// Exact import-resolution map built from the target package.
+    import_map: ImportMap,
+
+    /// Resolver used to check whether an imported module genuinely resolves
+    /// into the target boundary, honoring configured source roots, search
+    /// paths, and remappings, rather than a bare module-name comparison.
+    resolver: FileSystemResolver,
+
+    /// Canonicalized paths of every file inside the target boundary.
+    target_boundary: HashSet<PathBuf>,
 }
 
 impl ApiAnalyzer {
@@ -100,63 +245,84 @@ impl ApiAnalyzer {
     pub(crate) fn new(
         candidates: HashMap<String, DefinedSymbol>,
         target_module_name: String,
+        import_map: ImportMap,
+        resolver: FileSystemResolver,
+        target_boundary: HashSet<PathBuf>,
     ) -> Self {
-        let usage_counts = Arc::new(Mutex::new(
-            candidates
-                .iter()
-                .map(|(name, _)| (name.clone(), (0, HashSet::new())))
-                .collect::<SymbolUsageMap>(),
-        ));
-
         Self {
             candidates,
-            usage_counts,
             target_module_name,
+            import_map,
+            resolver,
+            target_boundary,
         }
     }
 
-    /// Check if a symbol is in the candidates list
-    pub(crate) fn is_candidate_symbol(&self, symbol: &str) -> bool {
-        self.candidates.contains_key(symbol)
-    }
-
-    /// Record usage of a symbol in a file
-    pub(crate) fn record_symbol_usage(&self, symbol: &str, file_path: &Path) -> Result<()> {
-        let mut usage_map = self
-            .usage_counts
-            .lock()
-            .map_err(|_| anyhow::anyhow!("Failed to acquire lock on usage counts"))?;
-
-        if let Some(entry) = usage_map.get_mut(symbol) {
-            entry.0 += 1;
-            entry.1.insert(file_path.to_path_buf());
+    /// Whether `module_name`, as imported from `importer_path`, names the
+    /// analysis target.
+    ///
+    /// Resolves the import through the configured `resolver` first, so the
+    /// answer honors source roots, search paths, and remappings rather than
+    /// assuming the target always sits at the dotted name's leading
+    /// component. Falls back to that bare comparison only when resolution
+    /// finds nothing on disk (e.g. in unit tests that construct an
+    /// `ApiAnalyzer` without a populated resolver).
+    pub(crate) fn import_names_target(&self, importer_path: &Path, module_name: &str) -> bool {
+        match self.resolver.resolve_from(importer_path, module_name) {
+            Some(resolved) => self
+                .target_boundary
+                .contains(&canonicalize_path(&resolved.path)),
+            None => {
+                module_name.split('.').next().unwrap_or(module_name) == self.target_module_name
+            }
         }
+    }
 
-        Ok(())
+    /// Resolve an imported `(module, name)` pair to the bare name of the
+    /// candidate it canonically refers to, if any.
+    ///
+    /// This replaces the old substring heuristics: the import map records the
+    /// exact canonical fully-qualified name each `(module, name)` binds to
+    /// (following re-export chains), so a candidate matches only when its own
+    /// fully-qualified name is identical.
+    ///
+    /// `module` is the *resolved absolute* module path, so relative imports
+    /// (`from . import foo`) match here just like absolute ones once the caller
+    /// has resolved their `level`. When the import map has no entry for the pair
+    /// — which happens for an intra-package import straight to a defining module
+    /// that is not itself re-exported — we fall back to reconstructing the
+    /// absolute qualified name (`module.name`) and comparing it directly against
+    /// the candidate's `fully_qualified_name`.
+    pub(crate) fn resolve_candidate(&self, module: &str, name: &str) -> Option<&str> {
+        let symbol = self.candidates.get(name)?;
+        let canonical = self
+            .import_map
+            .resolve(module, name)
+            .map(ToString::to_string)
+            .unwrap_or_else(|| format!("{}.{}", module, name));
+        (symbol.fully_qualified_name == canonical).then(|| name)
     }
 
-    /// Build the final list of API symbols
-    pub(crate) fn build_api_symbols(&self) -> Result<Vec<ApiSymbol>> {
-        // Convert usage data to API symbols list
-        let final_usage_counts = match Arc::try_unwrap(self.usage_counts.clone()) {
-            Ok(mutex) => mutex.into_inner()?,
-            Err(arc) => arc
-                .lock()
-                .map_err(|_| anyhow::anyhow!("Failed to acquire lock"))?
-                .clone(),
-        };
+    /// Check if a symbol is in the candidates list
+    pub(crate) fn is_candidate_symbol(&self, symbol: &str) -> bool {
+        self.candidates.contains_key(symbol)
+    }
 
+    /// Build the final list of API symbols from a reduced usage map.
+    pub(crate) fn build_api_symbols(&self, usage_counts: &SymbolUsageMap) -> Vec<ApiSymbol> {
         // Convert usage counts to API symbols list
         let mut public_api = Vec::new();
 
-        for (symbol_name, (count, importers)) in final_usage_counts {
-            if count > 0 {
-                if let Some(definition) = self.candidates.get(&symbol_name) {
+        for (symbol_name, tally) in usage_counts {
+            if tally.count > 0 {
+                if let Some(definition) = self.candidates.get(symbol_name) {
                     public_api.push(ApiSymbol {
-                        name: symbol_name,
+                        name: symbol_name.clone(),
                         definition: definition.clone(),
-                        usage_count: count,
-                        importers,
+                        usage_count: tally.count,
+                        importers: tally.importers.clone(),
+                        reexport_path: Vec::new(),
+                        by_role: tally.by_role.clone(),
                     });
                 }
             }
@@ -165,7 +331,34 @@ impl ApiAnalyzer {
         // Sort by name for consistent output
         public_api.sort_by(|a, b| a.name.cmp(&b.name));
 
-        Ok(public_api)
+        public_api
+    }
+
+    /// Build the list of public symbols that were *never* used externally.
+    ///
+    /// These are the interesting results for CI emitters (SARIF/JUnit): a
+    /// public symbol with zero external usages is dead exported surface.
+    pub(crate) fn build_unused_public_symbols(&self, usage_counts: &SymbolUsageMap) -> Vec<ApiSymbol> {
+        let mut unused = Vec::new();
+        for (name, definition) in &self.candidates {
+            if !definition.is_public {
+                continue;
+            }
+            let count = usage_counts.get(name).map_or(0, |tally| tally.count);
+            if count == 0 {
+                unused.push(ApiSymbol {
+                    name: name.clone(),
+                    definition: definition.clone(),
+                    usage_count: 0,
+                    importers: HashSet::new(),
+                    reexport_path: Vec::new(),
+                    by_role: std::collections::BTreeMap::new(),
+                });
+            }
+        }
+
+        unused.sort_by(|a, b| a.name.cmp(&b.name));
+        unused
     }
 }
 
@@ -174,11 +367,13 @@ impl ApiAnalyzer {
 //------------------------------------------------------------------------------
 
 /// Symbol kinds we can detect and report on
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 enum SymbolKind {
     Function,
     Class,
     Variable,
+    Constant,
+    TypeAlias,
     Module,
     Other,
 }
@@ -189,6 +384,8 @@ impl std::fmt::Display for SymbolKind {
             Self::Function => write!(f, "function"),
             Self::Class => write!(f, "class"),
             Self::Variable => write!(f, "variable"),
+            Self::Constant => write!(f, "constant"),
+            Self::TypeAlias => write!(f, "type alias"),
             Self::Module => write!(f, "module"),
             Self::Other => write!(f, "other"),
         }
@@ -196,13 +393,15 @@ impl std::fmt::Display for SymbolKind {
 }
 
 /// Information about a symbol defined in the target module/package
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct DefinedSymbol {
     kind: SymbolKind,
     location: PathBuf,
     docstring: Option<String>,
     is_public: bool,              // Based on naming convention and __all__
     fully_qualified_name: String, // Complete import path for the symbol
+    #[serde(default)]
+    type_checking_only: bool, // Defined only under `if TYPE_CHECKING:`
 }
 
 /// Information about a symbol's usage in the codebase
@@ -212,250 +411,1130 @@ pub(crate) struct ApiSymbol {
     definition: DefinedSymbol,
     usage_count: usize,
     importers: HashSet<PathBuf>, // Files that import and use this symbol
+    reexport_path: Vec<String>,  // Facade modules a consumer reaches it through
+    by_role: std::collections::BTreeMap<ConsumerRole, usize>, // Usage split by consumer role
+}
+
+impl ApiSymbol {
+    /// Render the non-zero consumer-role breakdown as `2 production, 1 test`,
+    /// or an empty string when no role information was recorded.
+    fn role_breakdown(&self) -> String {
+        ConsumerRole::ORDER
+            .iter()
+            .filter_map(|role| {
+                let count = self.by_role.get(role).copied().unwrap_or(0);
+                (count > 0).then(|| format!("{} {}", count, role))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 }
 
 //------------------------------------------------------------------------------
-// AST Visitor implementation for API analysis
+// Import resolution map
 //------------------------------------------------------------------------------
 
-/// Trait defining a visitor for Python AST traversal
-pub(crate) trait AstVisitor {
-    /// Visit a statement node
-    fn visit_stmt(&mut self, stmt: &ast::Stmt);
-
-    /// Visit an expression node
-    fn visit_expr(&mut self, expr: &ast::Expr);
+/// An exact map from `(module path, imported name)` to the canonical
+/// fully-qualified name that pair resolves to within the target package.
+///
+/// It records both names a module *defines* and names it *re-exports* (e.g.
+/// `from .sub import X` in an `__init__.py`), following re-export chains to
+/// their canonical definition. Matching an imported name is then an exact
+/// lookup rather than the fragile `starts_with`/`ends_with` string guessing,
+/// which both over- and under-matched.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ImportMap {
+    /// module path -> (local name -> canonical fully-qualified name)
+    modules: HashMap<String, HashMap<String, String>>,
 }
 
-/// Implementation of the Visitor pattern for API analysis
-pub(crate) struct ApiAnalyzerVisitor<'a> {
-    /// Current file being processed
-    file_path: &'a Path,
-
-    /// Reference to the analyzer with shared state
-    analyzer: &'a ApiAnalyzer,
+impl ImportMap {
+    /// Build the import map from the target package's files.
+    fn build(target_files: &[(PathBuf, ResolvedFile)]) -> Self {
+        let mut map = ImportMap::default();
 
-    /// File-specific state for the current file
-    file_state: &'a mut FileAnalysisState,
-}
+        // First pass: record every name each module defines.
+        for (path, resolved_file) in target_files {
+            let Ok(content) = fs::read_to_string(resolved_file.path()) else {
+                continue;
+            };
+            let Ok(parsed) = ruff_python_parser::parse_module(&content) else {
+                continue;
+            };
+            let module = get_module_name_from_path(path);
+            let entry = map.modules.entry(module.clone()).or_default();
 
-impl<'a> ApiAnalyzerVisitor<'a> {
-    /// Create a new visitor instance
-    pub(crate) fn new(
-        file_path: &'a Path,
-        analyzer: &'a ApiAnalyzer,
-        file_state: &'a mut FileAnalysisState,
-    ) -> Self {
-        Self {
-            file_path,
-            analyzer,
-            file_state,
+            for stmt in &parsed.syntax().body {
+                let name = match stmt {
+                    ast::Stmt::ClassDef(c) => Some(c.name.to_string()),
+                    ast::Stmt::FunctionDef(f) => Some(f.name.to_string()),
+                    ast::Stmt::Assign(a) => a.targets.iter().find_map(|t| match t {
+                        ast::Expr::Name(n) if n.id.as_str() != "__all__" => {
+                            Some(n.id.to_string())
+                        }
+                        _ => None,
+                    }),
+                    ast::Stmt::AnnAssign(a) => match a.target.as_ref() {
+                        ast::Expr::Name(n) if n.id.as_str() != "__all__" => {
+                            Some(n.id.to_string())
+                        }
+                        _ => None,
+                    },
+                    ast::Stmt::TypeAlias(t) => match t.name.as_ref() {
+                        ast::Expr::Name(n) => Some(n.id.to_string()),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if let Some(name) = name {
+                    let fqn = format!("{}.{}", module, name);
+                    entry.insert(name, fqn);
+                }
+            }
         }
-    }
-
-    /// Process an import statement to track module imports and their aliases
-    pub(crate) fn process_imports(&mut self, statements: &[ast::Stmt]) {
-        for stmt in statements {
-            match stmt {
-                ast::Stmt::Import(import) => {
-                    // Handle direct imports
-                    for alias in &import.names {
-                        let module_name = alias.name.as_str();
 
-                        // Track module imports and their aliases
-                        if let Some(asname) = &alias.asname {
-                            self.file_state
-                                .register_module_alias(asname.to_string(), module_name.to_string());
-                        } else {
-                            self.file_state.register_module_alias(
-                                module_name.to_string(),
-                                module_name.to_string(),
-                            );
-                        }
+        // Second pass: record re-exports, then resolve chains to a fixpoint.
+        map.collect_reexports(target_files);
+        map.resolve_chains();
+        map
+    }
 
-                        // Identify the module name without path
-                        let simple_module_name =
-                            module_name.split('.').next().unwrap_or(module_name);
+    /// Record `from <module> import <name>` re-export edges for each module.
+    fn collect_reexports(&mut self, target_files: &[(PathBuf, ResolvedFile)]) {
+        for (path, resolved_file) in target_files {
+            let Ok(content) = fs::read_to_string(resolved_file.path()) else {
+                continue;
+            };
+            let Ok(parsed) = ruff_python_parser::parse_module(&content) else {
+                continue;
+            };
+            let module = get_module_name_from_path(path);
+            let package = module_package(path, &module);
 
-                        // Check if this module being imported is our target module
-                        if simple_module_name == self.analyzer.target_module_name {
-                            // Mark the module itself as imported from our target
-                            self.file_state
-                                .register_imported_symbol(module_name.to_string());
-                        }
+            for stmt in &parsed.syntax().body {
+                let ast::Stmt::ImportFrom(import_from) = stmt else {
+                    continue;
+                };
+                let Some(source) = resolve_from_module(import_from, &package) else {
+                    continue;
+                };
+                for alias in &import_from.names {
+                    let imported = alias.name.as_str();
+                    if imported == "*" {
+                        continue;
+                    }
+                    let local = alias.asname.as_ref().map_or(imported, |a| a.as_str());
+                    // Bind to the source's canonical fqn; resolved later.
+                    let target = format!("{}.{}", source, imported);
+                    self.modules
+                        .entry(module.clone())
+                        .or_default()
+                        .entry(local.to_string())
+                        // A local definition always wins over a re-export.
+                        .or_insert(target);
+                }
+            }
+        }
+    }
 
-                        // Check if the module is one of our candidate symbols
-                        if self.analyzer.is_candidate_symbol(simple_module_name)
-                            && !self.file_state.is_processed(simple_module_name)
-                        {
-                            if let Err(e) = self
-                                .analyzer
-                                .record_symbol_usage(simple_module_name, self.file_path)
-                            {
-                                debug!("Error recording symbol usage: {}", e);
+    /// Follow re-export chains until every entry points to a canonical symbol
+    /// or stabilizes (bounded to avoid cycles).
+    fn resolve_chains(&mut self) {
+        for _ in 0..8 {
+            let mut changed = false;
+            let snapshot = self.modules.clone();
+            for names in self.modules.values_mut() {
+                for canonical in names.values_mut() {
+                    if let Some((module, name)) = canonical.rsplit_once('.') {
+                        if let Some(next) = snapshot.get(module).and_then(|m| m.get(name)) {
+                            if next != canonical {
+                                *canonical = next.clone();
+                                changed = true;
                             }
-                            self.file_state
-                                .mark_processed(simple_module_name.to_string());
-                            // Track this symbol as being imported from our target
-                            self.file_state
-                                .register_imported_symbol(module_name.to_string());
                         }
                     }
                 }
-                ast::Stmt::ImportFrom(import_from) => {
-                    // Handle from-imports
-                    if let Some(module_name) = &import_from.module {
-                        // When importing a module with "from", track what was imported
-                        let module_name_str = module_name.to_string();
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
 
-                        // Check if this is an import from our target module
-                        let simple_module_name = module_name_str
-                            .split('.')
-                            .next()
-                            .unwrap_or(&module_name_str);
-                        let is_target_module =
-                            simple_module_name == self.analyzer.target_module_name;
+    /// Resolve an imported `(module, name)` to its canonical fully-qualified
+    /// name, if the target package defines or re-exports it.
+    fn resolve(&self, module: &str, name: &str) -> Option<&str> {
+        self.modules
+            .get(module)
+            .and_then(|names| names.get(name))
+            .map(String::as_str)
+    }
+}
 
-                        for alias in &import_from.names {
-                            let name = alias.name.as_str();
+/// Compute the package path that owns a module (its own path for `__init__.py`,
+/// otherwise its parent package).
+fn module_package(path: &Path, module: &str) -> String {
+    let is_init = path.file_stem().and_then(|s| s.to_str()) == Some("__init__");
+    if is_init {
+        module.to_string()
+    } else {
+        module.rsplit_once('.').map_or(String::new(), |(pkg, _)| pkg.to_string())
+    }
+}
 
-                            // Handle "from pkg1 import pkg2" case
-                            if let Some(asname) = &alias.asname {
-                                self.file_state
-                                    .register_module_alias(asname.to_string(), name.to_string());
-                            } else {
-                                self.file_state
-                                    .register_module_alias(name.to_string(), name.to_string());
-                            }
+/// Resolve the absolute source module of an `ImportFrom`, honoring relative
+/// `level` against the importing module's `package`.
+fn resolve_from_module(import_from: &ast::StmtImportFrom, package: &str) -> Option<String> {
+    let module = import_from.module.as_ref().map(ToString::to_string);
+    if import_from.level == 0 {
+        return module;
+    }
 
-                            // If this is an import from our target module, add it to the imported_symbols
-                            if is_target_module {
-                                self.file_state.register_imported_symbol(name.to_string());
-                            }
+    let mut components: Vec<&str> = if package.is_empty() {
+        Vec::new()
+    } else {
+        package.split('.').collect()
+    };
+    let ascend = (import_from.level - 1) as usize;
+    if ascend > components.len() {
+        return None;
+    }
+    components.truncate(components.len() - ascend);
+
+    let base = components.join(".");
+    match module {
+        Some(module) if base.is_empty() => Some(module),
+        Some(module) => Some(format!("{}.{}", base, module)),
+        None if base.is_empty() => None,
+        None => Some(base),
+    }
+}
 
-                            // Check if the imported symbol is in our candidates by comparing both base name and fully qualified name
-                            if self.analyzer.is_candidate_symbol(name)
-                                && !self.file_state.is_processed(name)
-                            {
-                                // Construct the expected fully qualified name directly
-                                let mut expected_fqn = module_name_str.clone();
-                                expected_fqn.push('.');
-                                expected_fqn.push_str(name);
+//------------------------------------------------------------------------------
+// Module import graph
+//------------------------------------------------------------------------------
 
-                                // Get the candidate symbol
-                                let matching = self
-                                    .analyzer
-                                    .candidates
-                                    .get(name)
-                                    .map(|sym| {
-                                        // Only consider it a match if the fully qualified name matches or starts with the expected FQN
-                                        sym.fully_qualified_name == expected_fqn
-                                            || expected_fqn.starts_with(&sym.fully_qualified_name)
-                                            || sym.fully_qualified_name.ends_with(&expected_fqn)
-                                    })
-                                    .unwrap_or(false);
-
-                                // Only count usage if the fully qualified name matches
-                                if matching {
-                                    if let Err(e) =
-                                        self.analyzer.record_symbol_usage(name, self.file_path)
-                                    {
-                                        debug!("Error recording symbol usage: {}", e);
-                                    }
-                                    self.file_state.mark_processed(name.to_string());
-                                }
-                            }
+/// A circular import: the modules forming a cycle, ending with the module that
+/// closes the back edge (so the first and last element are the same name).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CircularImport {
+    cycle: Vec<ModuleName>,
+}
+
+/// A directed graph of intra-project module imports: an edge `a -> b` means
+/// module `a` imports from module `b`. Built while parsing each file, it powers
+/// circular-import detection. Only
+/// edges whose target resolves to another project module are recorded;
+/// unresolvable or optional imports become non-fatal missing edges.
+#[derive(Debug, Default)]
+pub(crate) struct ImportGraph {
+    edges: std::collections::BTreeMap<ModuleName, std::collections::BTreeSet<ModuleName>>,
+}
+
+impl ImportGraph {
+    /// Build the import graph from the project's files. A file's imports are
+    /// resolved against the set of module names the project actually defines, so
+    /// third-party and optional imports simply produce no edge.
+    fn build(files: &[(PathBuf, ResolvedFile)]) -> Self {
+        let project_modules: std::collections::BTreeSet<ModuleName> = files
+            .iter()
+            .map(|(path, _)| get_module_name_from_path(path))
+            .collect();
+
+        let mut graph = ImportGraph::default();
+        for (path, resolved_file) in files {
+            let Ok(content) = fs::read_to_string(resolved_file.path()) else {
+                continue;
+            };
+            let Ok(parsed) = ruff_python_parser::parse_module(&content) else {
+                continue;
+            };
+            let importer = get_module_name_from_path(path);
+            let package = module_package(path, &importer);
+            graph.edges.entry(importer.clone()).or_default();
+
+            for stmt in &parsed.syntax().body {
+                match stmt {
+                    ast::Stmt::Import(import) => {
+                        for alias in &import.names {
+                            graph.add_edge(&importer, alias.name.as_str(), &project_modules);
                         }
                     }
+                    ast::Stmt::ImportFrom(import_from) => {
+                        if let Some(source) = resolve_from_module(import_from, &package) {
+                            graph.add_edge(&importer, &source, &project_modules);
+                        }
+                    }
+                    _ => {}
                 }
-                _ => {}
             }
         }
+        graph
     }
 
-    /// Record usage of a symbol
-    fn record_symbol_usage(&self, symbol: &str) {
-        if self.analyzer.is_candidate_symbol(symbol)
-            && self.file_state.is_imported_from_target(symbol)
-            && !self.file_state.is_processed(symbol)
-        {
-            if let Err(e) = self.analyzer.record_symbol_usage(symbol, self.file_path) {
-                debug!("Error recording symbol usage: {}", e);
-            }
+    /// Record an edge to `target` only when it names another project module and
+    /// is not a self-import.
+    fn add_edge(
+        &mut self,
+        importer: &str,
+        target: &str,
+        project_modules: &std::collections::BTreeSet<ModuleName>,
+    ) {
+        if target != importer && project_modules.contains(target) {
+            self.edges
+                .entry(importer.to_string())
+                .or_default()
+                .insert(target.to_string());
         }
     }
 
-    /// Check for module.symbol pattern and record if found
-    fn check_attribute_access(&self, attr: &ast::ExprAttribute) {
-        if let ast::Expr::Name(name) = &attr.value.as_ref() {
-            let module_alias = name.id.as_str();
-
-            // If this is a module we've imported
-            if let Some(actual_module_name) = self.file_state.get_actual_module_name(module_alias) {
-                let accessed_attr = attr.attr.as_str();
+    /// Detect every circular import via a depth-first search with a recursion
+    /// stack (gray/black coloring): when the search re-enters a module already
+    /// on the current stack, the slice of the stack from that module to the top
+    /// is the cycle closed by the back edge.
+    fn circular_imports(&self) -> Vec<CircularImport> {
+        let mut black: std::collections::BTreeSet<&str> = std::collections::BTreeSet::new();
+        let mut cycles = Vec::new();
+
+        for node in self.edges.keys() {
+            if black.contains(node.as_str()) {
+                continue;
+            }
+            let mut stack: Vec<&str> = Vec::new();
+            let mut gray: std::collections::BTreeSet<&str> = std::collections::BTreeSet::new();
+            self.visit(node, &mut stack, &mut gray, &mut black, &mut cycles);
+        }
 
-                // Check if this symbol is in our candidates
-                if self.analyzer.is_candidate_symbol(accessed_attr)
-                    && !self.file_state.is_processed(accessed_attr)
-                {
-                    // Avoid redundant format! calls by constructing the expected FQN directly
-                    let mut expected_fqn = actual_module_name.to_owned();
-                    expected_fqn.push('.');
-                    expected_fqn.push_str(accessed_attr);
+        cycles
+    }
 
-                    // Get the candidate symbol and check if its fully qualified name matches
-                    let matching = self
-                        .analyzer
-                        .candidates
-                        .get(accessed_attr)
-                        .map(|sym| {
-                            // Only consider it a match if the fully qualified name matches or ends with the expected FQN
-                            sym.fully_qualified_name == expected_fqn
-                                || expected_fqn.starts_with(&sym.fully_qualified_name)
-                                || sym.fully_qualified_name.ends_with(&expected_fqn)
-                        })
-                        .unwrap_or(false);
-
-                    // Check if the module is our target module or an alias to it
-                    let simple_module = actual_module_name
-                        .split('.')
-                        .next()
-                        .unwrap_or(actual_module_name);
-                    let is_target_module = simple_module == self.analyzer.target_module_name;
-
-                    if is_target_module && matching {
-                        if let Err(e) = self
-                            .analyzer
-                            .record_symbol_usage(accessed_attr, self.file_path)
-                        {
-                            debug!("Error recording symbol usage: {}", e);
-                        }
+    /// DFS helper: `gray` holds the modules currently on `stack`, `black` the
+    /// ones fully explored.
+    fn visit<'a>(
+        &'a self,
+        node: &'a str,
+        stack: &mut Vec<&'a str>,
+        gray: &mut std::collections::BTreeSet<&'a str>,
+        black: &mut std::collections::BTreeSet<&'a str>,
+        cycles: &mut Vec<CircularImport>,
+    ) {
+        stack.push(node);
+        gray.insert(node);
+
+        if let Some(targets) = self.edges.get(node) {
+            for target in targets {
+                if gray.contains(target.as_str()) {
+                    // Back edge: reconstruct the cycle from the stack.
+                    if let Some(start) = stack.iter().position(|m| *m == target.as_str()) {
+                        let mut cycle: Vec<ModuleName> =
+                            stack[start..].iter().map(|m| m.to_string()).collect();
+                        cycle.push(target.clone());
+                        cycles.push(CircularImport { cycle });
                     }
+                } else if !black.contains(target.as_str()) {
+                    self.visit(target, stack, gray, black, cycles);
                 }
             }
         }
+
+        gray.remove(node);
+        black.insert(node);
+        stack.pop();
     }
 }
 
-impl<'a> AstVisitor for ApiAnalyzerVisitor<'a> {
-    fn visit_stmt(&mut self, stmt: &ast::Stmt) {
-        match stmt {
-            // Expression statement (standalone expression)
-            ast::Stmt::Expr(expr_stmt) => {
-                self.visit_expr(&expr_stmt.value);
-            }
+//------------------------------------------------------------------------------
+// Re-export path annotation (reporting only)
+//------------------------------------------------------------------------------
 
-            // Assignment statement
-            ast::Stmt::Assign(assign) => {
-                self.visit_expr(&assign.value);
-                for target in &assign.targets {
-                    self.visit_expr(target);
-                }
-            }
+/// Where an imported module lives. A `Local` module is part of the target tree
+/// and can be followed; anything else is `Missing` (third-party or unresolved)
+/// and terminates a re-export chain.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ImportLocation {
+    Local(PathBuf),
+    Missing,
+}
 
-            // Augmented assignment (e.g., x += 1)
-            ast::Stmt::AugAssign(aug_assign) => {
-                self.visit_expr(&aug_assign.value);
+/// The export table of a single module, computed once and cached.
+#[derive(Debug, Default, Clone)]
+struct ResolvedModule {
+    /// Names defined directly in this module.
+    defined: HashSet<String>,
+    /// `local name -> (source module, source name)` re-export edges, e.g.
+    /// `from .sub import X as Y` records `Y -> (pkg.sub, X)`.
+    reexports: HashMap<String, (String, String)>,
+    /// Source modules pulled in wholesale via `from <mod> import *`.
+    star_sources: Vec<String>,
+}
+
+/// A resolver environment that follows re-export chains to the module that
+/// actually *defines* a symbol, modeled on Dhall's import resolver: an
+/// [`ImportCache`](ResolveEnv::cache) parses and tabulates each module file at
+/// most once, and an [`ImportStack`](ResolveEnv::stack) breaks the cycles that
+/// mutual `__init__` imports would otherwise spin on forever.
+///
+/// This only produces the `reexport_path` annotation shown in reports (and
+/// the public-re-export promotion in `analyze_api`); it does not affect usage
+/// crediting. A use of a re-exported name is already attributed to its
+/// defining symbol by [`ImportMap::resolve_chains`], which every candidate
+/// lookup goes through regardless of whether this environment runs.
+pub(crate) struct ResolveEnv {
+    /// Qualified module name -> source file on disk.
+    module_paths: HashMap<String, PathBuf>,
+    /// Parsed export tables, keyed by location (the import cache).
+    cache: HashMap<ImportLocation, ResolvedModule>,
+    /// Modules currently on the resolution path (the import stack).
+    stack: Vec<ImportLocation>,
+}
+
+/// The outcome of resolving an imported `(module, name)`: the canonical
+/// fully-qualified name it binds to, plus the chain of modules the resolver
+/// walked through to reach it.
+#[derive(Debug, Clone)]
+struct Resolution {
+    fully_qualified_name: String,
+    reexport_path: Vec<String>,
+}
+
+impl ResolveEnv {
+    /// Build a resolver over the target package's files.
+    fn build(target_files: &[(PathBuf, ResolvedFile)]) -> Self {
+        let mut module_paths = HashMap::new();
+        for (path, resolved_file) in target_files {
+            let module = get_module_name_from_path(path);
+            module_paths.insert(module, resolved_file.path().to_path_buf());
+        }
+        Self {
+            module_paths,
+            cache: HashMap::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    /// The on-disk location of a module, or [`ImportLocation::Missing`] when it
+    /// is not part of the target tree.
+    fn location(&self, module: &str) -> ImportLocation {
+        self.module_paths
+            .get(module)
+            .map_or(ImportLocation::Missing, |path| {
+                ImportLocation::Local(path.clone())
+            })
+    }
+
+    /// Return the (cached) export table for a module location, parsing the file
+    /// the first time it is requested.
+    fn module_table(&mut self, module: &str, loc: &ImportLocation) -> ResolvedModule {
+        if let Some(cached) = self.cache.get(loc) {
+            return cached.clone();
+        }
+
+        let table = match loc {
+            ImportLocation::Local(path) => Self::build_table(module, path),
+            ImportLocation::Missing => ResolvedModule::default(),
+        };
+        self.cache.insert(loc.clone(), table.clone());
+        table
+    }
+
+    /// Parse a module file and tabulate what it defines and re-exports.
+    fn build_table(module: &str, path: &Path) -> ResolvedModule {
+        let mut table = ResolvedModule::default();
+        let Ok(content) = fs::read_to_string(path) else {
+            return table;
+        };
+        let Ok(parsed) = ruff_python_parser::parse_module(&content) else {
+            return table;
+        };
+        let package = module_package(path, module);
+
+        for stmt in &parsed.syntax().body {
+            match stmt {
+                ast::Stmt::FunctionDef(f) => {
+                    table.defined.insert(f.name.to_string());
+                }
+                ast::Stmt::ClassDef(c) => {
+                    table.defined.insert(c.name.to_string());
+                }
+                ast::Stmt::Assign(assign) => {
+                    for target in &assign.targets {
+                        if let ast::Expr::Name(name) = target {
+                            table.defined.insert(name.id.to_string());
+                        }
+                    }
+                }
+                ast::Stmt::AnnAssign(ann_assign) => {
+                    if let ast::Expr::Name(name) = ann_assign.target.as_ref() {
+                        table.defined.insert(name.id.to_string());
+                    }
+                }
+                ast::Stmt::TypeAlias(type_alias) => {
+                    if let ast::Expr::Name(name) = type_alias.name.as_ref() {
+                        table.defined.insert(name.id.to_string());
+                    }
+                }
+                ast::Stmt::ImportFrom(import_from) => {
+                    let Some(source) = resolve_from_module(import_from, &package) else {
+                        continue;
+                    };
+                    for alias in &import_from.names {
+                        let imported = alias.name.as_str();
+                        if imported == "*" {
+                            table.star_sources.push(source.clone());
+                            continue;
+                        }
+                        let local = alias.asname.as_ref().map_or(imported, |a| a.as_str());
+                        table
+                            .reexports
+                            .entry(local.to_string())
+                            .or_insert_with(|| (source.clone(), imported.to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        table
+    }
+
+    /// Resolve `(module, name)` to the symbol it ultimately refers to, walking
+    /// re-export edges (including `*` imports). Returns `None` for third-party
+    /// modules, names that are neither defined nor re-exported, and back edges
+    /// into a module already being resolved (cycle).
+    fn resolve(&mut self, module: &str, name: &str) -> Option<Resolution> {
+        let loc = self.location(module);
+        if self.stack.contains(&loc) {
+            // A module re-importing a name that routes back to itself: stop
+            // this branch rather than recursing forever.
+            return None;
+        }
+        self.stack.push(loc.clone());
+
+        let table = self.module_table(module, &loc);
+        let result = if let Some((source_module, source_name)) = table.reexports.get(name) {
+            let (source_module, source_name) = (source_module.clone(), source_name.clone());
+            self.resolve(&source_module, &source_name)
+                .map(|mut res| {
+                    res.reexport_path.insert(0, module.to_string());
+                    res
+                })
+        } else if table.defined.contains(name) {
+            Some(Resolution {
+                fully_qualified_name: format!("{}.{}", module, name),
+                reexport_path: vec![module.to_string()],
+            })
+        } else {
+            // Fall back to any `from <src> import *` re-exports.
+            let mut found = None;
+            for source in table.star_sources.clone() {
+                if let Some(mut res) = self.resolve(&source, name) {
+                    res.reexport_path.insert(0, module.to_string());
+                    found = Some(res);
+                    break;
+                }
+            }
+            found
+        };
+
+        self.stack.pop();
+        result
+    }
+
+    /// The re-export path by which a symbol is exposed on its top-level package
+    /// facade, or an empty path when it is not re-exported (reached directly in
+    /// its defining module).
+    fn reexport_path_for(&mut self, definition: &DefinedSymbol) -> Vec<String> {
+        let fqn = definition.fully_qualified_name.clone();
+        let Some((module, name)) = fqn.rsplit_once('.') else {
+            return Vec::new();
+        };
+        let top = fqn.split('.').next().unwrap_or(&fqn);
+        if top == module {
+            // Defined in a top-level module; no facade to traverse.
+            return Vec::new();
+        }
+
+        match self.resolve(top, name) {
+            Some(res) if res.fully_qualified_name == fqn && res.reexport_path.len() > 1 => {
+                res.reexport_path
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// Fuzzy symbol search
+//------------------------------------------------------------------------------
+
+/// A fuzzy search index over the candidate symbol names.
+///
+/// The names are stored in a sorted finite-state transducer ([`fst::Map`])
+/// mapping `name -> index into names`. A query runs a Levenshtein automaton
+/// against the transducer, so the match set is found by intersecting two
+/// automata and the cost is sublinear in the number of symbols rather than a
+/// per-symbol edit-distance computation.
+pub(crate) struct SymbolSearch {
+    /// `name -> sorted insertion index`, as a finite-state transducer. The
+    /// keys themselves carry the names; matches are decoded straight from them.
+    map: fst::Map<Vec<u8>>,
+}
+
+/// A single fuzzy-search hit, carrying the data needed to rank it.
+struct SearchHit {
+    name: String,
+    /// Edit distance from the query (lower is better).
+    distance: u32,
+}
+
+impl SymbolSearch {
+    /// Build a search index from the analyzer's candidate set.
+    ///
+    /// Returns `None` when there are no candidates, since an empty transducer
+    /// has nothing to search.
+    pub(crate) fn from_candidates(candidates: &HashMap<String, DefinedSymbol>) -> Option<Self> {
+        if candidates.is_empty() {
+            return None;
+        }
+
+        // `fst::Map` requires keys inserted in lexicographic order.
+        let mut names: Vec<String> = candidates.keys().cloned().collect();
+        names.sort();
+
+        let mut builder = fst::MapBuilder::memory();
+        for (index, name) in names.iter().enumerate() {
+            // Indices are bounded by the candidate count, well within u64.
+            builder
+                .insert(name, index as u64)
+                .expect("names are sorted and unique");
+        }
+        let map = fst::Map::new(builder.into_inner().ok()?).ok()?;
+
+        Some(Self { map })
+    }
+
+    /// Find candidates within `max_distance` edits of `query`, ranked best
+    /// first, and resolve each to an [`ApiSymbol`] via `usage`.
+    ///
+    /// `max_distance` is clamped to the 1..=2 range the Levenshtein automaton
+    /// supports cheaply. Ranking prefers, in order: smaller edit distance, an
+    /// exact case-sensitive prefix match, a case-insensitive prefix match, then
+    /// lexicographic order for a stable result.
+    pub(crate) fn search(
+        &self,
+        query: &str,
+        max_distance: u8,
+        usage: &SymbolUsageMap,
+        candidates: &HashMap<String, DefinedSymbol>,
+    ) -> Result<Vec<ApiSymbol>> {
+        use fst::{automaton::Levenshtein, IntoStreamer, Streamer};
+
+        let distance = max_distance.clamp(1, 2) as u32;
+        let automaton = Levenshtein::new(query, distance)
+            .map_err(|e| anyhow::anyhow!("invalid search query {query:?}: {e}"))?;
+
+        let mut hits = Vec::new();
+        let mut stream = self.map.search_with_state(&automaton).into_stream();
+        while let Some((key, _value, state)) = stream.next() {
+            let name = String::from_utf8_lossy(key).into_owned();
+            // The automaton's final state records the distance reached.
+            let distance = automaton
+                .distance(state)
+                .to_u8()
+                .map_or(u32::from(max_distance), u32::from);
+            hits.push(SearchHit { name, distance });
+        }
+
+        hits.sort_by(|a, b| self.rank_key(query, a).cmp(&self.rank_key(query, b)));
+
+        let results = hits
+            .into_iter()
+            .filter_map(|hit| {
+                let definition = candidates.get(&hit.name)?.clone();
+                let tally = usage.get(&hit.name).cloned().unwrap_or_default();
+                Some(ApiSymbol {
+                    name: hit.name,
+                    definition,
+                    usage_count: tally.count,
+                    importers: tally.importers,
+                    reexport_path: Vec::new(),
+                    by_role: tally.by_role,
+                })
+            })
+            .collect();
+
+        Ok(results)
+    }
+
+    /// The sort key used to rank a hit: `(distance, not-exact-prefix,
+    /// not-ci-prefix, name)`. Booleans are inverted so that `false` (a match)
+    /// sorts before `true`.
+    fn rank_key(&self, query: &str, hit: &SearchHit) -> (u32, bool, bool, String) {
+        let exact_prefix = !hit.name.starts_with(query);
+        let ci_prefix = !hit
+            .name
+            .to_lowercase()
+            .starts_with(&query.to_lowercase());
+        (hit.distance, exact_prefix, ci_prefix, hit.name.clone())
+    }
+}
+
+//------------------------------------------------------------------------------
+// Public-API baseline snapshots and breaking-change diffing
+//------------------------------------------------------------------------------
+
+/// A single entry in a serialized public-API snapshot.
+///
+/// Entries are intentionally small and stable so that snapshots serialized on
+/// different machines (or during parallel runs) compare byte-for-byte when the
+/// underlying surface is unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SymbolSnapshot {
+    /// The dotted module path that owns the symbol (may be empty for top-level).
+    module: String,
+    /// The bare symbol name.
+    symbol: String,
+    /// The symbol kind, rendered via [`SymbolKind::Display`].
+    kind: String,
+    /// How many external usages were attributed to the symbol.
+    usage_count: usize,
+}
+
+impl SymbolSnapshot {
+    /// The `module::symbol` key used for line-based diffs.
+    fn key(&self) -> String {
+        if self.module.is_empty() {
+            self.symbol.clone()
+        } else {
+            format!("{}::{}", self.module, self.symbol)
+        }
+    }
+}
+
+/// Classification of a single symbol delta between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SymbolChange {
+    /// A public symbol present in the new run but not the baseline.
+    Added(SymbolSnapshot),
+    /// A public symbol that disappeared — a potential breaking change.
+    Removed(SymbolSnapshot),
+    /// A symbol whose kind changed between runs.
+    Changed {
+        old: SymbolSnapshot,
+        new: SymbolSnapshot,
+    },
+}
+
+/// Build a fully deterministic snapshot of the public API surface.
+///
+/// The ordering is sorted by module path then symbol name so that diffs are
+/// stable regardless of the order in which files were analyzed.
+fn build_symbol_snapshot(public_api: &[ApiSymbol]) -> Vec<SymbolSnapshot> {
+    let mut snapshot: Vec<SymbolSnapshot> = public_api
+        .iter()
+        .map(|sym| {
+            let fqn = &sym.definition.fully_qualified_name;
+            let (module, symbol) = match fqn.rsplit_once('.') {
+                Some((module, symbol)) => (module.to_string(), symbol.to_string()),
+                None => (String::new(), fqn.clone()),
+            };
+            SymbolSnapshot {
+                module,
+                symbol,
+                kind: sym.definition.kind.to_string(),
+                usage_count: sym.usage_count,
+            }
+        })
+        .collect();
+
+    snapshot.sort_by(|a, b| a.module.cmp(&b.module).then_with(|| a.symbol.cmp(&b.symbol)));
+    snapshot
+}
+
+/// Load a baseline snapshot from a JSON file.
+fn load_baseline(path: &Path) -> Result<Vec<SymbolSnapshot>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read baseline {}: {}", path.display(), e))?;
+    let snapshot = serde_json::from_str(&contents)
+        .map_err(|e| anyhow::anyhow!("Failed to parse baseline {}: {}", path.display(), e))?;
+    Ok(snapshot)
+}
+
+/// Write a baseline snapshot to a JSON file (used by `--bless`).
+fn write_baseline(path: &Path, snapshot: &[SymbolSnapshot]) -> Result<()> {
+    let json = serde_json::to_string_pretty(snapshot)?;
+    fs::write(path, json)
+        .map_err(|e| anyhow::anyhow!("Failed to write baseline {}: {}", path.display(), e))?;
+    Ok(())
+}
+
+/// Classify every delta between a baseline and a freshly computed snapshot.
+fn diff_snapshots(baseline: &[SymbolSnapshot], current: &[SymbolSnapshot]) -> Vec<SymbolChange> {
+    let baseline_by_key: HashMap<String, &SymbolSnapshot> =
+        baseline.iter().map(|s| (s.key(), s)).collect();
+    let current_by_key: HashMap<String, &SymbolSnapshot> =
+        current.iter().map(|s| (s.key(), s)).collect();
+
+    let mut changes = Vec::new();
+
+    // REMOVED / CHANGED are discovered by walking the baseline.
+    for entry in baseline {
+        match current_by_key.get(&entry.key()) {
+            None => changes.push(SymbolChange::Removed(entry.clone())),
+            Some(new) if new.kind != entry.kind => changes.push(SymbolChange::Changed {
+                old: entry.clone(),
+                new: (*new).clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    // ADDED is whatever the current run has that the baseline did not.
+    for entry in current {
+        if !baseline_by_key.contains_key(&entry.key()) {
+            changes.push(SymbolChange::Added(entry.clone()));
+        }
+    }
+
+    changes
+}
+
+/// Render a unified, Myers-style line diff over the sorted `module::symbol`
+/// keys of two snapshots.
+fn render_unified_diff(baseline: &[SymbolSnapshot], current: &[SymbolSnapshot]) -> String {
+    let old_lines: Vec<String> = baseline.iter().map(SymbolSnapshot::key).collect();
+    let new_lines: Vec<String> = current.iter().map(SymbolSnapshot::key).collect();
+    unified_line_diff(&old_lines, &new_lines)
+}
+
+/// Produce a unified diff of two line sequences using a longest-common-
+/// subsequence backtrace (the same core Myers computes). Common lines are
+/// prefixed with a space, removals with `-`, and additions with `+`.
+fn unified_line_diff(old: &[String], new: &[String]) -> String {
+    let (n, m) = (old.len(), new.len());
+
+    // lcs[i][j] = length of the LCS of old[i..] and new[j..].
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            out.push_str(&format!("  {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push_str(&format!("- {}\n", old[i]));
+            i += 1;
+        } else {
+            out.push_str(&format!("+ {}\n", new[j]));
+            j += 1;
+        }
+    }
+    for line in &old[i..] {
+        out.push_str(&format!("- {}\n", line));
+    }
+    for line in &new[j..] {
+        out.push_str(&format!("+ {}\n", line));
+    }
+    out
+}
+
+//------------------------------------------------------------------------------
+// AST Visitor implementation for API analysis
+//------------------------------------------------------------------------------
+
+/// Trait defining a visitor for Python AST traversal
+pub(crate) trait AstVisitor {
+    /// Visit a statement node
+    fn visit_stmt(&mut self, stmt: &ast::Stmt);
+
+    /// Visit an expression node
+    fn visit_expr(&mut self, expr: &ast::Expr);
+}
+
+/// Implementation of the Visitor pattern for API analysis
+pub(crate) struct ApiAnalyzerVisitor<'a> {
+    /// Current file being processed
+    file_path: &'a Path,
+
+    /// Reference to the analyzer with shared (read-only) state
+    analyzer: &'a ApiAnalyzer,
+
+    /// File-specific state for the current file
+    file_state: &'a mut FileAnalysisState,
+
+    /// The consumer role of the file being analyzed, tagged onto every usage it
+    /// contributes so the driver can aggregate counts per role.
+    role: ConsumerRole,
+
+    /// Usage counts accumulated for this file alone. The driver reduces these
+    /// per-file maps together, so the visitor never touches shared state.
+    usage: SymbolUsageMap,
+}
+
+impl<'a> ApiAnalyzerVisitor<'a> {
+    /// Create a new visitor instance
+    pub(crate) fn new(
+        file_path: &'a Path,
+        analyzer: &'a ApiAnalyzer,
+        file_state: &'a mut FileAnalysisState,
+        role: ConsumerRole,
+    ) -> Self {
+        Self {
+            file_path,
+            analyzer,
+            file_state,
+            role,
+            usage: SymbolUsageMap::new(),
+        }
+    }
+
+    /// Consume the visitor and return the usage counts gathered for this file.
+    pub(crate) fn into_usage(self) -> SymbolUsageMap {
+        self.usage
+    }
+
+    /// Record one usage of `symbol`, tagged with `role`, into this file's local
+    /// usage map.
+    fn record_into(usage: &mut SymbolUsageMap, file_path: &Path, symbol: &str, role: ConsumerRole) {
+        usage
+            .entry(symbol.to_string())
+            .or_default()
+            .record(file_path, role);
+    }
+
+    /// Process an import statement to track module imports and their aliases
+    pub(crate) fn process_imports(&mut self, statements: &[ast::Stmt]) {
+        for stmt in statements {
+            match stmt {
+                ast::Stmt::Import(import) => {
+                    // Handle direct imports
+                    for alias in &import.names {
+                        let module_name = alias.name.as_str();
+
+                        // Track module imports and their aliases
+                        if let Some(asname) = &alias.asname {
+                            self.file_state
+                                .register_module_alias(asname.to_string(), module_name.to_string());
+                        } else {
+                            self.file_state.register_module_alias(
+                                module_name.to_string(),
+                                module_name.to_string(),
+                            );
+                        }
+
+                        // Identify the module name without path
+                        let simple_module_name =
+                            module_name.split('.').next().unwrap_or(module_name);
+
+                        // Check if this module being imported is our target module
+                        if self
+                            .analyzer
+                            .import_names_target(self.file_path, module_name)
+                        {
+                            // Mark the module itself as imported from our target
+                            self.file_state
+                                .register_imported_symbol(module_name.to_string());
+                        }
+
+                        // Check if the module is one of our candidate symbols
+                        if self.analyzer.is_candidate_symbol(simple_module_name)
+                            && !self.file_state.is_processed(simple_module_name)
+                        {
+                            Self::record_into(
+                                &mut self.usage,
+                                self.file_path,
+                                simple_module_name,
+                                self.role,
+                            );
+                            self.file_state
+                                .mark_processed(simple_module_name.to_string());
+                            // Track this symbol as being imported from our target
+                            self.file_state
+                                .register_imported_symbol(module_name.to_string());
+                        }
+                    }
+                }
+                ast::Stmt::ImportFrom(import_from) => {
+                    // Handle from-imports, resolving relative (`level`-prefixed)
+                    // imports to their absolute module path first.
+                    if let Some(module_name_str) = self.resolve_import_module(import_from) {
+                        // Check if this is an import from our target module
+                        let is_target_module = self
+                            .analyzer
+                            .import_names_target(self.file_path, &module_name_str);
+
+                        for alias in &import_from.names {
+                            let name = alias.name.as_str();
+
+                            // Handle `from target import *`: a glob pulls in the
+                            // module's public surface only. Register every
+                            // exported candidate defined in this module so the
+                            // later `Expr::Name` path counts bare uses of them.
+                            if name == "*" {
+                                let module_prefix = format!("{}.", module_name_str);
+                                for (candidate_name, symbol) in &self.analyzer.candidates {
+                                    let defined_here = symbol
+                                        .fully_qualified_name
+                                        .strip_prefix(&module_prefix)
+                                        .map_or(false, |rest| rest == candidate_name.as_str());
+                                    // A glob only brings in `__all__` / non-underscore
+                                    // public names, which `is_public` already encodes.
+                                    if defined_here && symbol.is_public {
+                                        self.file_state
+                                            .register_imported_symbol(candidate_name.clone());
+                                    }
+                                }
+                                continue;
+                            }
+
+                            // Handle "from pkg1 import pkg2" case
+                            if let Some(asname) = &alias.asname {
+                                self.file_state
+                                    .register_module_alias(asname.to_string(), name.to_string());
+                            } else {
+                                self.file_state
+                                    .register_module_alias(name.to_string(), name.to_string());
+                            }
+
+                            // If this is an import from our target module, add it to the imported_symbols
+                            if is_target_module {
+                                self.file_state.register_imported_symbol(name.to_string());
+                            }
+
+                            // Resolve the imported name through the import map
+                            // and count it only when it resolves to this exact
+                            // candidate (following re-export chains).
+                            if self.analyzer.is_candidate_symbol(name)
+                                && !self.file_state.is_processed(name)
+                                && self
+                                    .analyzer
+                                    .resolve_candidate(&module_name_str, name)
+                                    .is_some()
+                            {
+                                Self::record_into(&mut self.usage, self.file_path, name, self.role);
+                                self.file_state.mark_processed(name.to_string());
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Resolve the absolute module path an `ImportFrom` statement refers to,
+    /// following the relative-import `level` when present.
+    ///
+    /// `from . import x` resolves to the importing file's own package; each
+    /// extra leading dot walks up one more package. Imports that walk above the
+    /// package root are treated as unresolved.
+    fn resolve_import_module(&self, import_from: &ast::StmtImportFrom) -> Option<String> {
+        let module = import_from.module.as_ref().map(ToString::to_string);
+
+        // Absolute import: nothing to resolve.
+        if import_from.level == 0 {
+            return module;
+        }
+
+        // Compute the importing file's own package components.
+        let importer_module = get_module_name_from_path(self.file_path);
+        let is_init =
+            self.file_path.file_stem().and_then(|s| s.to_str()) == Some("__init__");
+        let mut components: Vec<&str> = importer_module.split('.').collect();
+        if !is_init {
+            // Drop the filename component to reach the enclosing package.
+            components.pop();
+        }
+
+        // `level` 1 is the current package; each further level drops one more.
+        let ascend = (import_from.level - 1) as usize;
+        if ascend > components.len() {
+            debug!(
+                "Relative import walks above package root in {}",
+                self.file_path.display()
+            );
+            return None;
+        }
+        components.truncate(components.len() - ascend);
+
+        let base = components.join(".");
+        let resolved = match module {
+            Some(module) if base.is_empty() => module,
+            Some(module) => format!("{}.{}", base, module),
+            None if base.is_empty() => return None,
+            None => base,
+        };
+
+        Some(resolved)
+    }
+
+    /// Record usage of a symbol
+    fn record_symbol_usage(&mut self, symbol: &str) {
+        if self.analyzer.is_candidate_symbol(symbol)
+            && self.file_state.is_imported_from_target(symbol)
+            && !self.file_state.is_processed(symbol)
+        {
+            Self::record_into(&mut self.usage, self.file_path, symbol, self.role);
+        }
+    }
+
+    /// Check for module.symbol pattern and record if found
+    fn check_attribute_access(&mut self, attr: &ast::ExprAttribute) {
+        if let ast::Expr::Name(name) = &attr.value.as_ref() {
+            let module_alias = name.id.as_str();
+
+            // If this is a module we've imported
+            if let Some(actual_module_name) =
+                self.file_state.get_actual_module_name(module_alias).map(str::to_string)
+            {
+                let accessed_attr = attr.attr.as_str();
+
+                // Check if this symbol is in our candidates and resolves,
+                // through the import map, to this exact candidate.
+                if self.analyzer.is_candidate_symbol(accessed_attr)
+                    && !self.file_state.is_processed(accessed_attr)
+                    && self
+                        .analyzer
+                        .resolve_candidate(&actual_module_name, accessed_attr)
+                        .is_some()
+                {
+                    Self::record_into(&mut self.usage, self.file_path, accessed_attr, self.role);
+                }
+            }
+        }
+    }
+}
+
+impl<'a> AstVisitor for ApiAnalyzerVisitor<'a> {
+    fn visit_stmt(&mut self, stmt: &ast::Stmt) {
+        match stmt {
+            // Expression statement (standalone expression)
+            ast::Stmt::Expr(expr_stmt) => {
+                self.visit_expr(&expr_stmt.value);
+            }
+
+            // Assignment statement
+            ast::Stmt::Assign(assign) => {
+                self.visit_expr(&assign.value);
+                for target in &assign.targets {
+                    self.visit_expr(target);
+                }
+            }
+
+            // Augmented assignment (e.g., x += 1)
+            ast::Stmt::AugAssign(aug_assign) => {
+                self.visit_expr(&aug_assign.value);
                 self.visit_expr(&aug_assign.target);
             }
 
@@ -725,11 +1804,40 @@ impl<'a> AstVisitor for ApiAnalyzerVisitor<'a> {
 pub fn analyze_api(
     args: &AnalyzeApiArgs,
     config_arguments: &ConfigArguments,
+) -> Result<ExitStatus> {
+    analyze_api_with_timings(args, config_arguments, None)
+}
+
+/// Per-phase wall-clock timings for a single `analyze_api` run. Collected by
+/// the `bench` subcommand to show where time in a large package goes, instead of one
+/// opaque end-to-end duration.
+#[derive(Debug, Clone, Default)]
+pub struct PhaseTimings {
+    /// Detecting the project root/config and partitioning files into target
+    /// vs. external.
+    pub discover: Duration,
+    /// Parsing target files into candidate symbols.
+    pub parse: Duration,
+    /// Building the import map and import graph.
+    pub resolve: Duration,
+    /// Attributing external usage against the candidate symbols.
+    pub analyze: Duration,
+}
+
+/// Same as [`analyze_api`], but also records per-phase timings into `timings`
+/// when it's `Some`. Timing is opt-in so the normal analysis path pays no
+/// `Instant::now()` overhead.
+pub fn analyze_api_with_timings(
+    args: &AnalyzeApiArgs,
+    config_arguments: &ConfigArguments,
+    mut timings: Option<&mut PhaseTimings>,
 ) -> Result<ExitStatus> {
     // Resolve project configuration
     let pyproject_config = resolve::resolve(config_arguments, None)?;
     let _settings = &pyproject_config.settings;
 
+    let discover_start = Instant::now();
+
     info!("Analyzing API for: {}", args.target_path.display());
     if args.no_parallel {
         info!("Parallel processing disabled, using sequential implementation");
@@ -755,6 +1863,46 @@ pub fn analyze_api(
         detected_root
     };
 
+    // Discover layered configuration ([tool.pubscan]) unless running isolated.
+    let config = PubscanConfig::discover(&project_root, args.isolated);
+
+    // `python` names the venv whose `site-packages` should be treated as a
+    // (non-first-party) search path, so imports satisfied by installed
+    // dependencies resolve as reachable rather than third-party-and-skipped.
+    // The CLI flag wins over the configured value, same as every other
+    // resolver input.
+    let venv_site_packages = args
+        .python
+        .as_deref()
+        .or(config.python.as_deref())
+        .and_then(site_packages_from_python);
+
+    // Build the module resolver from config + CLI (the latter wins by coming
+    // last). Its first-party source roots seed project file discovery so that
+    // non-standard layouts still have their target files found and classified.
+    let module_resolver = FileSystemResolver::new(
+        &project_root,
+        config
+            .source_roots
+            .iter()
+            .chain(args.source_roots.iter())
+            .cloned()
+            .collect(),
+        config
+            .search_paths
+            .iter()
+            .chain(args.search_paths.iter())
+            .cloned()
+            .chain(venv_site_packages)
+            .collect(),
+        &config
+            .remappings
+            .iter()
+            .chain(args.remappings.iter())
+            .cloned()
+            .collect::<Vec<_>>(),
+    );
+
     // Check if target is within the project root
     let target_canonical = fs::canonicalize(&args.target_path)?;
     let project_canonical = fs::canonicalize(&project_root)?;
@@ -768,7 +1916,15 @@ pub fn analyze_api(
         );
     }
 
-    let files = resolve_default_files(vec![project_root.to_path_buf()], false);
+    // Scan every first-party source root, de-duplicated, so extra roots declared
+    // for a non-standard layout contribute their files too.
+    let mut scan_roots: Vec<PathBuf> = Vec::new();
+    for root in module_resolver.source_roots() {
+        if !scan_roots.contains(root) {
+            scan_roots.push(root.clone());
+        }
+    }
+    let files = resolve_default_files(scan_roots, false);
     let (paths, resolver) = python_files_in_path(&files, &pyproject_config, config_arguments)?;
 
     if paths.is_empty() {
@@ -815,19 +1971,48 @@ pub fn analyze_api(
         }
     }
 
+    // Classify external consumers by role (production/test/example/bench),
+    // honoring any directory-name overrides from the discovered config.
+    let classifier = RoleClassifier::new(
+        &config.test_dirs,
+        &config.example_dirs,
+        &config.bench_dirs,
+    );
+
+    // By default, test consumers are left out of the scan entirely rather than
+    // merely tagged: `no_ignore_test_files` opts back in, and
+    // `test_file_patterns` extends the directory/filename convention
+    // `classifier` already applies with project-specific glob patterns.
+    let keep_test_files = args.no_ignore_test_files || config.no_ignore_test_files.unwrap_or(false);
+    let is_ignored_test_file = |path: &Path| -> bool {
+        if keep_test_files {
+            return false;
+        }
+        if classifier.classify(path) == ConsumerRole::Test {
+            return true;
+        }
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| matches_any_pattern(name, &config.test_file_patterns))
+    };
+
     // Now process all files from the project
     for resolved_result in paths {
         if let Ok(resolved_file) = resolved_result {
             let path = resolved_file.path().to_path_buf();
             trace!("Considering path: {}", path.display());
 
-            // Determine if this file is within the target boundary
-            if is_file_within_target(&target_boundary, &path) {
+            // Canonicalize once here and reuse the result for the membership
+            // test against the boundary set.
+            let canonical = canonicalize_path(&path);
+            if is_file_within_target(&target_boundary, &canonical) {
                 trace!("Added to target files: {}", path.display());
-                target_files.push((path.clone(), resolved_file));
+                target_files.push((path, resolved_file));
+            } else if is_ignored_test_file(&path) {
+                trace!("Ignoring test file: {}", path.display());
             } else {
                 trace!("Added to external files: {}", path.display());
-                external_files.push((path.clone(), resolved_file));
+                external_files.push((path, resolved_file));
             }
         }
     }
@@ -838,37 +2023,270 @@ pub fn analyze_api(
         external_files.len()
     );
 
+    if let Some(t) = timings.as_deref_mut() {
+        t.discover = discover_start.elapsed();
+    }
+
+    // `--strict` runs an independent boundary-violation pass over the whole
+    // project and returns immediately: it flags private/undefined cross-module
+    // accesses rather than reporting the effective public API, so it never
+    // enters the candidate/usage pipeline below.
+    if args.strict {
+        let mut all_files: Vec<PathBuf> = target_files
+            .iter()
+            .chain(external_files.iter())
+            .map(|(path, _)| path.clone())
+            .collect();
+        all_files.sort();
+        all_files.dedup();
+
+        let diagnostics = analyze_package_strict(PackageRoot::root(&project_root), &all_files);
+        print_strict_diagnostics(&diagnostics);
+        return Ok(if diagnostics.is_empty() {
+            ExitStatus::Success
+        } else {
+            ExitStatus::Failure
+        });
+    }
+
     if target_files.is_empty() {
         info!("No Python files found in the target path");
         return Ok(ExitStatus::Success);
     }
 
-    // Extract candidate symbols from target files
-    let candidate_symbols = extract_candidate_symbols(&target_files, &resolver)?;
+    // Set up the incremental fact cache unless disabled.
+    let mut fact_cache = if args.no_cache {
+        None
+    } else {
+        let cache_dir = args
+            .cache_dir
+            .clone()
+            .unwrap_or_else(|| project_root.join(".pubscan_cache"));
+        Some(FactCache::load(&cache_dir))
+    };
+
+    let parse_start = Instant::now();
+
+    // Extract candidate symbols from target files, reusing cached facts.
+    let mut candidate_symbols =
+        extract_candidate_symbols(&target_files, &resolver, fact_cache.as_mut())?;
+
+    if let Some(cache) = &fact_cache {
+        if let Err(e) = cache.save() {
+            debug!("Failed to persist fact cache: {}", e);
+        }
+    }
+
+    // Promote symbols re-exported through a package facade (`__init__.py`) to
+    // public. A name a package deliberately re-exports is part of that package's
+    // public surface even when its defining-module name or absence from a local
+    // `__all__` would otherwise mark it private; usage of the re-exported name
+    // then attributes back to the original definition.
+    {
+        let mut reexport_env = ResolveEnv::build(&target_files);
+        for symbol in candidate_symbols.values_mut() {
+            if !symbol.is_public && !reexport_env.reexport_path_for(symbol).is_empty() {
+                symbol.is_public = true;
+            }
+        }
+    }
 
     debug!(
         "Found {} candidate symbols in target",
         candidate_symbols.len()
     );
 
+    if let Some(t) = timings.as_deref_mut() {
+        t.parse = parse_start.elapsed();
+    }
+
+    let resolve_start = Instant::now();
+
     // Determine the target module name for more accurate attribute accesses tracking
     let target_module_name = determine_target_module_name(&candidate_symbols);
 
+    // Build the exact import-resolution map from the target package.
+    let import_map = ImportMap::build(&target_files);
+
+    // Build the module import graph over every project file and surface any
+    // circular imports. Cycles are reported, not fatal: analysis continues.
+    let import_graph = ImportGraph::build(
+        &target_files
+            .iter()
+            .chain(external_files.iter())
+            .cloned()
+            .collect::<Vec<_>>(),
+    );
+    for cycle in import_graph.circular_imports() {
+        info!("Circular import: {}", cycle.cycle.join(" -> "));
+    }
+
+    if let Some(t) = timings.as_deref_mut() {
+        t.resolve = resolve_start.elapsed();
+    }
+
+    let analyze_start = Instant::now();
+
     // Use the semantic model approach for more accurate attribute access detection
-    let public_api = analyze_external_with_semantic_model(
+    let (mut public_api, unused_public) = analyze_external_with_semantic_model(
         &candidate_symbols,
         &external_files,
         &resolver,
+        &module_resolver,
+        &target_boundary,
         &target_module_name,
+        import_map,
+        &classifier,
         args.no_parallel,
     )?;
 
+    if let Some(t) = timings.as_deref_mut() {
+        t.analyze = analyze_start.elapsed();
+    }
+
+    // `--production-only` narrows the report to symbols that have at least one
+    // production consumer, hiding API reachable only from tests/examples.
+    if args.production_only {
+        public_api.retain(|sym| sym.by_role.get(&ConsumerRole::Production).copied().unwrap_or(0) > 0);
+    }
+
+    // Annotate re-exported symbols: record the facade chain each public symbol
+    // is reachable through, for reporting only. Usage was already credited to
+    // the original definition through the import map above.
+    let mut resolve_env = ResolveEnv::build(&target_files);
+    for symbol in &mut public_api {
+        symbol.reexport_path = resolve_env.reexport_path_for(&symbol.definition);
+    }
+
+    // `--package-report` aggregates usage across the package's own sibling
+    // files, independent of the external-consumer pipeline above, so a symbol
+    // with external consumers but no internal callers still surfaces as
+    // "defined but never consumed" within the package itself.
+    if args.package_report {
+        let package_files: Vec<PathBuf> =
+            target_files.iter().map(|(path, _)| path.clone()).collect();
+        let report = analyze_package_api_with_parallelism(
+            PackageRoot::root(&project_root),
+            &package_files,
+            !args.no_parallel,
+        );
+        print_package_report(&report);
+    }
+
+    // `search` mode fuzzy-matches candidate names and prints only those hits,
+    // skipping the full report.
+    if let Some(query) = &args.search_query {
+        let usage: SymbolUsageMap = public_api
+            .iter()
+            .map(|sym| {
+                (
+                    sym.name.clone(),
+                    UsageTally {
+                        count: sym.usage_count,
+                        importers: sym.importers.clone(),
+                        by_role: sym.by_role.clone(),
+                    },
+                )
+            })
+            .collect();
+        let matches = match SymbolSearch::from_candidates(&candidate_symbols) {
+            Some(index) => {
+                index.search(query, args.search_distance, &usage, &candidate_symbols)?
+            }
+            None => Vec::new(),
+        };
+        print_search_results(query, &matches);
+        return Ok(ExitStatus::Success);
+    }
+
+    // `--bless` snapshots the current surface and skips the comparison entirely.
+    if args.bless {
+        let Some(baseline_path) = &args.baseline else {
+            anyhow::bail!("--bless requires --baseline <file> to know where to write the snapshot");
+        };
+        let snapshot = build_symbol_snapshot(&public_api);
+        write_baseline(baseline_path, &snapshot)?;
+        info!(
+            "Blessed baseline {} with {} public symbols",
+            baseline_path.display(),
+            snapshot.len()
+        );
+        return Ok(ExitStatus::Success);
+    }
+
+    // Symbols on the always-public allowlist are entry points with no in-repo
+    // callers; never report them as unused.
+    let unused_public: Vec<ApiSymbol> = unused_public
+        .into_iter()
+        .filter(|sym| !config.is_always_public(sym))
+        .collect();
+
+    // Resolve the effective output format: CLI flag overrides config default.
+    let effective_format = args
+        .output_format
+        .clone()
+        .or_else(|| config.output_format.clone())
+        .unwrap_or_else(|| "text".to_string());
+
     // Output the results
-    output_results(&public_api, args)?;
+    output_results(&public_api, &unused_public, &effective_format, args)?;
+
+    // When a baseline is supplied, compare against it and gate on removals.
+    if let Some(baseline_path) = &args.baseline {
+        return Ok(compare_against_baseline(baseline_path, &public_api)?);
+    }
 
     Ok(ExitStatus::Success)
 }
 
+/// Compare the freshly computed public API against a baseline snapshot,
+/// rendering a unified diff and classifying each delta. Returns
+/// [`ExitStatus::Failure`] when any symbol was REMOVED so the run gates CI.
+fn compare_against_baseline(baseline_path: &Path, public_api: &[ApiSymbol]) -> Result<ExitStatus> {
+    use colored::Colorize;
+
+    let baseline = load_baseline(baseline_path)?;
+    let current = build_symbol_snapshot(public_api);
+    let changes = diff_snapshots(&baseline, &current);
+
+    if changes.is_empty() {
+        println!("Public API is unchanged against {}.", baseline_path.display());
+        return Ok(ExitStatus::Success);
+    }
+
+    println!("Public API diff against {}:", baseline_path.display());
+    print!("{}", render_unified_diff(&baseline, &current));
+    println!();
+
+    let mut removed = 0;
+    for change in &changes {
+        match change {
+            SymbolChange::Added(s) => println!("{} {}", "ADDED".green(), s.key()),
+            SymbolChange::Removed(s) => {
+                removed += 1;
+                println!("{} {}", "REMOVED".red(), s.key());
+            }
+            SymbolChange::Changed { old, new } => println!(
+                "{} {} ({} -> {})",
+                "CHANGED".yellow(),
+                new.key(),
+                old.kind,
+                new.kind
+            ),
+        }
+    }
+
+    if removed > 0 {
+        println!(
+            "\n{} public symbol(s) removed — treating as a breaking change.",
+            removed
+        );
+        Ok(ExitStatus::Failure)
+    } else {
+        Ok(ExitStatus::Success)
+    }
+}
+
 //------------------------------------------------------------------------------
 // Project and file analysis functions
 //------------------------------------------------------------------------------
@@ -878,87 +2296,424 @@ fn analyze_external_with_semantic_model(
     candidates: &HashMap<String, DefinedSymbol>,
     external_files: &ResolvedFileCollection,
     _resolver: &Resolver,
+    module_resolver: &FileSystemResolver,
+    target_boundary: &HashSet<PathBuf>,
     target_module_name: &str,
+    import_map: ImportMap,
+    classifier: &RoleClassifier,
     no_parallel: bool,
-) -> Result<Vec<ApiSymbol>> {
-    // Create an ApiAnalyzer instance to manage shared state
-    let analyzer = ApiAnalyzer::new(candidates.clone(), target_module_name.to_string());
+) -> Result<(Vec<ApiSymbol>, Vec<ApiSymbol>)> {
+    // Create an ApiAnalyzer holding the read-only shared state (candidates,
+    // the import map, and the module resolver). No mutable state lives here:
+    // each file produces its own local usage map, which the map-reduce below
+    // combines.
+    let analyzer = ApiAnalyzer::new(
+        candidates.clone(),
+        target_module_name.to_string(),
+        import_map,
+        module_resolver.clone(),
+        target_boundary.clone(),
+    );
 
-    // This check uses the no_parallel parameter directly instead of analyzer.no_parallel
-    if no_parallel {
-        // Process external files sequentially
+    // Reduce the per-file usage maps into a single one, either sequentially or
+    // across rayon's thread pool. Combining owned maps needs no shared lock.
+    let usage = if no_parallel {
         debug!(
             "Processing {} external files sequentially",
             external_files.len()
         );
-        external_files.iter().for_each(|(path, resolved_file)| {
-            debug!("Analyzing external file: {}", path.display());
-
-            // Read and parse the file content
-            match std::fs::read_to_string(resolved_file.path()) {
-                Ok(file_content) => {
-                    if let Ok(parsed) = ruff_python_parser::parse_module(&file_content) {
-                        // Create per-file analysis state
-                        let mut file_state = FileAnalysisState::new();
-
-                        // Create a visitor for this file
-                        let mut visitor = ApiAnalyzerVisitor::new(path, &analyzer, &mut file_state);
-
-                        // First pass: identify module imports and aliases
-                        visitor.process_imports(&parsed.syntax().body);
-
-                        // Second pass: use the visitor to scan the entire module for API usage
-                        for stmt in &parsed.syntax().body {
-                            visitor.visit_stmt(stmt);
-                        }
-                    }
-                }
-                Err(e) => {
-                    debug!("Error reading file {}: {}", path.display(), e);
-                }
-            }
-        });
+        external_files
+            .iter()
+            .map(|(path, resolved_file)| {
+                analyze_file_usage(path, resolved_file, &analyzer, classifier)
+            })
+            .fold(SymbolUsageMap::new(), merge_usage)
     } else {
-        // Process external files in parallel
         debug!(
             "Processing {} external files in parallel",
             external_files.len()
         );
-        external_files.par_iter().for_each(|(path, resolved_file)| {
-            debug!("Analyzing external file: {}", path.display());
+        external_files
+            .par_iter()
+            .map(|(path, resolved_file)| {
+                analyze_file_usage(path, resolved_file, &analyzer, classifier)
+            })
+            .reduce(SymbolUsageMap::new, merge_usage)
+    };
+
+    // Return the used API symbols along with the never-used public surface.
+    let public_api = analyzer.build_api_symbols(&usage);
+    let unused_public = analyzer.build_unused_public_symbols(&usage);
+    Ok((public_api, unused_public))
+}
 
-            // Read and parse the file content
-            match std::fs::read_to_string(resolved_file.path()) {
-                Ok(file_content) => {
-                    if let Ok(parsed) = ruff_python_parser::parse_module(&file_content) {
-                        // Create per-file analysis state
-                        let mut file_state = FileAnalysisState::new();
+/// Analyze a single external file and return the usage counts it contributes.
+///
+/// The returned map contains only the candidate symbols this file actually
+/// uses; the driver reduces these per-file maps together.
+fn analyze_file_usage(
+    path: &Path,
+    resolved_file: &ResolvedFile,
+    analyzer: &ApiAnalyzer,
+    classifier: &RoleClassifier,
+) -> SymbolUsageMap {
+    debug!("Analyzing external file: {}", path.display());
+
+    let file_content = match std::fs::read_to_string(resolved_file.path()) {
+        Ok(content) => content,
+        Err(e) => {
+            debug!("Error reading file {}: {}", path.display(), e);
+            return SymbolUsageMap::new();
+        }
+    };
+
+    let Ok(parsed) = ruff_python_parser::parse_module(&file_content) else {
+        return SymbolUsageMap::new();
+    };
 
-                        // Create a visitor for this file
-                        let mut visitor = ApiAnalyzerVisitor::new(path, &analyzer, &mut file_state);
+    // Per-file state and a visitor that accumulates into its own usage map,
+    // tagging every usage with the file's consumer role.
+    let role = classifier.classify(path);
+    let mut file_state = FileAnalysisState::new();
+    let mut visitor = ApiAnalyzerVisitor::new(path, analyzer, &mut file_state, role);
 
-                        // First pass: identify module imports and aliases
-                        visitor.process_imports(&parsed.syntax().body);
+    // First pass: identify module imports and aliases.
+    visitor.process_imports(&parsed.syntax().body);
 
-                        // Second pass: use the visitor to scan the entire module for API usage
-                        for stmt in &parsed.syntax().body {
-                            visitor.visit_stmt(stmt);
-                        }
+    // Second pass: scan the entire module for API usage.
+    for stmt in &parsed.syntax().body {
+        visitor.visit_stmt(stmt);
+    }
+
+    visitor.into_usage()
+}
+
+/// Merge one file's usage map into an accumulator, summing counts and unioning
+/// the importer sets. This is the reduce step of the per-file map-reduce.
+fn merge_usage(mut acc: SymbolUsageMap, other: SymbolUsageMap) -> SymbolUsageMap {
+    for (name, tally) in other {
+        acc.entry(name).or_default().merge(tally);
+    }
+    acc
+}
+
+/// Serializable view of an [`ApiSymbol`], shared by the `json` and
+/// `json-lines` emitters.
+#[derive(Serialize)]
+struct JsonApiSymbol {
+    name: String,
+    fully_qualified_name: String,
+    kind: String,
+    location: String,
+    docstring: Option<String>,
+    usage_count: usize,
+    importers: Vec<String>,
+    is_public: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    reexport_path: Vec<String>,
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    usage_by_role: std::collections::BTreeMap<ConsumerRole, usize>,
+}
+
+/// Convert an [`ApiSymbol`] into its serializable form.
+fn json_api_symbol(sym: &ApiSymbol) -> JsonApiSymbol {
+    JsonApiSymbol {
+        name: sym.name.clone(),
+        fully_qualified_name: sym.definition.fully_qualified_name.clone(),
+        kind: sym.definition.kind.to_string(),
+        location: sym.definition.location.display().to_string(),
+        docstring: sym.definition.docstring.clone(),
+        usage_count: sym.usage_count,
+        importers: sym
+            .importers
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect(),
+        is_public: sym.definition.is_public,
+        reexport_path: sym.reexport_path.clone(),
+        usage_by_role: sym.by_role.clone(),
+    }
+}
+
+/// Build a SARIF 2.1.0 document reporting every never-used public symbol as a
+/// result, so the findings drop into GitHub code scanning.
+fn build_sarif(unused_public: &[ApiSymbol], target_path: &Path) -> serde_json::Value {
+    use serde_json::json;
+
+    let results: Vec<serde_json::Value> = unused_public
+        .iter()
+        .map(|sym| {
+            json!({
+                "ruleId": "pubscan/unused-public-symbol",
+                "level": "warning",
+                "message": {
+                    "text": format!(
+                        "Public symbol `{}` is never used outside the target.",
+                        sym.definition.fully_qualified_name
+                    )
+                },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": sym.definition.location.display().to_string()
+                        },
+                        // Span information is not retained for definitions, so the
+                        // result anchors to the top of the defining file.
+                        "region": { "startLine": 1 }
                     }
+                }]
+            })
+        })
+        .collect();
+
+    json!({
+        "version": "2.1.0",
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "pubscan",
+                    "informationUri": "https://github.com/vivster7/pubscan",
+                    "rules": [{
+                        "id": "pubscan/unused-public-symbol",
+                        "shortDescription": {
+                            "text": "Public symbol with no external usages"
+                        }
+                    }]
                 }
-                Err(e) => {
-                    debug!("Error reading file {}: {}", path.display(), e);
+            },
+            "results": results,
+            "properties": { "target": target_path.display().to_string() }
+        }]
+    })
+}
+
+/// Build a JUnit XML report where each analyzed module is a testsuite and each
+/// dead-but-exported symbol is a failing testcase.
+fn build_junit(unused_public: &[ApiSymbol]) -> String {
+    // Group the never-used symbols by their defining module.
+    let mut by_module: std::collections::BTreeMap<String, Vec<&ApiSymbol>> =
+        std::collections::BTreeMap::new();
+    for sym in unused_public {
+        let module = sym
+            .definition
+            .fully_qualified_name
+            .rsplit_once('.')
+            .map_or_else(|| sym.definition.fully_qualified_name.clone(), |(m, _)| m.to_string());
+        by_module.entry(module).or_default().push(sym);
+    }
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<testsuites>\n");
+    for (module, symbols) in &by_module {
+        out.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+            xml_escape(module),
+            symbols.len(),
+            symbols.len()
+        ));
+        for sym in symbols {
+            out.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\">\n",
+                xml_escape(&sym.name),
+                xml_escape(module)
+            ));
+            out.push_str(&format!(
+                "      <failure message=\"unused public symbol\">{} is exported but never used externally</failure>\n",
+                xml_escape(&sym.definition.fully_qualified_name)
+            ));
+            out.push_str("    </testcase>\n");
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+/// Escape the five XML predefined entities for safe attribute/text output.
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// The display width of a string in terminal columns, counting wide (CJK)
+/// characters as two columns and zero-width characters as none.
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| unicode_width::UnicodeWidthChar::width(c).unwrap_or(0))
+        .sum()
+}
+
+/// Resolve the column width to wrap docstrings at: the CLI override if given,
+/// else the detected terminal width, falling back to 80. Never narrower than
+/// 20 columns so a tiny terminal still produces readable output.
+fn effective_wrap_width(args: &AnalyzeApiArgs) -> usize {
+    args.wrap_width
+        .or_else(|| terminal_size::terminal_size().map(|(w, _)| w.0 as usize))
+        .unwrap_or(80)
+        .max(20)
+}
+
+/// Wrap `text` to `width` columns using the minimum-raggedness ("optimal fit")
+/// algorithm rather than greedy first-fit.
+///
+/// Words are the only legal break points. The cost of a non-final line is the
+/// square of its trailing slack `(width - line_width)^2`; a line that overflows
+/// `width` is disallowed unless it is a single unavoidable word, and the final
+/// line pays no slack penalty. `cost[i]` — the cheapest layout of the first `i`
+/// words — is filled by dynamic programming and the breaks reconstructed from
+/// the recorded arg-mins.
+fn optimal_wrap(text: &str, width: usize) -> Vec<String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+    let n = words.len();
+    let widths: Vec<usize> = words.iter().map(|w| display_width(w)).collect();
+
+    const INF: u64 = u64::MAX / 4;
+    let mut cost = vec![INF; n + 1];
+    let mut break_at = vec![0usize; n + 1];
+    cost[0] = 0;
+
+    for i in 1..=n {
+        // The last line spans words `j..i`; widening it (smaller `j`) only adds
+        // columns, so we can stop the moment it overflows.
+        let mut j = i;
+        while j > 0 {
+            j -= 1;
+            let words_width: usize = widths[j..i].iter().sum();
+            let line_width = words_width + (i - j - 1); // one space between words
+
+            if line_width > width {
+                if i - j == 1 && cost[j] != INF && cost[j] < cost[i] {
+                    // A single word wider than `width` cannot be broken; place
+                    // it alone without a slack penalty.
+                    cost[i] = cost[j];
+                    break_at[i] = j;
                 }
+                break;
             }
-        });
+
+            let line_cost = if i == n {
+                0
+            } else {
+                let slack = (width - line_width) as u64;
+                slack * slack
+            };
+
+            if cost[j] != INF && cost[j] + line_cost < cost[i] {
+                cost[i] = cost[j] + line_cost;
+                break_at[i] = j;
+            }
+        }
+    }
+
+    let mut lines = Vec::new();
+    let mut i = n;
+    while i > 0 {
+        let j = break_at[i];
+        lines.push(words[j..i].join(" "));
+        i = j;
+    }
+    lines.reverse();
+    lines
+}
+
+/// Print the ranked results of a `search` query.
+fn print_search_results(query: &str, matches: &[ApiSymbol]) {
+    use colored::Colorize;
+
+    if matches.is_empty() {
+        println!("No symbols matching {:?}.", query);
+        return;
     }
 
-    // Return the API symbols
-    analyzer.build_api_symbols()
+    println!("Symbols matching {:?}:", query);
+    for symbol in matches {
+        let usage = if symbol.usage_count == 1 {
+            "1 external usage".to_string()
+        } else {
+            format!("{} external usages", symbol.usage_count)
+        };
+        println!(
+            "  {} ({}, {})",
+            symbol.name.cyan(),
+            symbol.definition.kind,
+            usage
+        );
+    }
+}
+
+/// Print the package-wide usage report from `--package-report`: every
+/// locally-defined symbol's aggregate usage across the package's own sibling
+/// files, and which of those files actually reference it.
+///
+/// Usage is attributed to the symbol's own defining module even when every
+/// caller reaches it through a re-export facade (`analyze_package_api`
+/// resolves the chain before crediting a reference), so a symbol can show
+/// consumers here despite `app.py` only ever importing it from `__init__.py`.
+fn print_package_report(report: &ruff_linter::api::ApiReport) {
+    let unused: Vec<_> = report.unused().collect();
+    if unused.is_empty() {
+        println!("Package report: every defined symbol is consumed somewhere in the package.");
+    } else {
+        println!("Package report: {} symbol(s) defined but never consumed within the package:", unused.len());
+        for symbol in unused {
+            println!("  {} (defined in {})", symbol.qualified_name, symbol.defined_in.display());
+        }
+    }
+
+    for symbol in report.symbols().filter(|sym| !sym.is_unused()) {
+        let referencing_files = symbol
+            .referencing_files
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "  {} used {} time(s), referenced by: {}",
+            symbol.qualified_name, symbol.usage_count, referencing_files
+        );
+    }
+}
+
+/// Print the diagnostics from a `--strict` boundary-violation pass.
+fn print_strict_diagnostics(diagnostics: &[ruff_linter::strict::Diagnostic]) {
+    use colored::Colorize;
+
+    if diagnostics.is_empty() {
+        println!("No boundary violations found.");
+        return;
+    }
+
+    for diagnostic in diagnostics {
+        let kind = match diagnostic.kind {
+            DiagnosticKind::Private => "private".red(),
+            DiagnosticKind::Undefined => "undefined".yellow(),
+        };
+        println!(
+            "{}:{}: {} access to {}",
+            diagnostic.file.display(),
+            diagnostic.line,
+            kind,
+            diagnostic.accessed
+        );
+    }
 }
 
 /// Output the results of the API analysis
-fn output_results(public_api: &[ApiSymbol], args: &AnalyzeApiArgs) -> Result<()> {
+fn output_results(
+    public_api: &[ApiSymbol],
+    unused_public: &[ApiSymbol],
+    format: &str,
+    args: &AnalyzeApiArgs,
+) -> Result<()> {
     use colored::Colorize;
 
     // Handle the short output format first
@@ -983,16 +2738,40 @@ fn output_results(public_api: &[ApiSymbol], args: &AnalyzeApiArgs) -> Result<()>
             } else {
                 format!("{} external usages", symbol.usage_count)
             };
-            // Print using the owned String or a string literal
-            println!("  {} ({})", symbol.name.cyan(), usage_output);
+            // Print using the owned String or a string literal, appending the
+            // per-role breakdown when one was recorded.
+            let breakdown = symbol.role_breakdown();
+            if breakdown.is_empty() {
+                println!("  {} ({})", symbol.name.cyan(), usage_output);
+            } else {
+                println!("  {} ({}: {})", symbol.name.cyan(), usage_output, breakdown);
+            }
         }
         return Ok(());
     }
 
     // Determine the output format for non-short output
-    let format = args.output_format.as_deref().unwrap_or("text");
-
     match format {
+        "json-lines" => {
+            // Stream one JSON record per line instead of buffering a document.
+            for sym in public_api {
+                let line = serde_json::to_string(&json_api_symbol(sym))?;
+                println!("{}", line);
+            }
+            return Ok(());
+        }
+
+        "sarif" => {
+            let sarif = build_sarif(unused_public, &args.target_path);
+            println!("{}", serde_json::to_string_pretty(&sarif)?);
+            return Ok(());
+        }
+
+        "junit" => {
+            print!("{}", build_junit(unused_public));
+            return Ok(());
+        }
+
         "json" => {
             // Create a serializable structure for JSON output
             #[derive(Serialize)]
@@ -1001,36 +2780,8 @@ fn output_results(public_api: &[ApiSymbol], args: &AnalyzeApiArgs) -> Result<()>
                 target_path: String,
             }
 
-            #[derive(Serialize)]
-            struct JsonApiSymbol {
-                name: String,
-                fully_qualified_name: String,
-                kind: String,
-                location: String,
-                docstring: Option<String>,
-                usage_count: usize,
-                importers: Vec<String>,
-                is_public: bool,
-            }
-
             // Convert our API symbols to the serializable format
-            let api_json: Vec<JsonApiSymbol> = public_api
-                .iter()
-                .map(|sym| JsonApiSymbol {
-                    name: sym.name.clone(),
-                    fully_qualified_name: sym.definition.fully_qualified_name.clone(),
-                    kind: sym.definition.kind.to_string(),
-                    location: sym.definition.location.display().to_string(),
-                    docstring: sym.definition.docstring.clone(),
-                    usage_count: sym.usage_count,
-                    importers: sym
-                        .importers
-                        .iter()
-                        .map(|p| p.display().to_string())
-                        .collect(),
-                    is_public: sym.definition.is_public,
-                })
-                .collect();
+            let api_json: Vec<JsonApiSymbol> = public_api.iter().map(json_api_symbol).collect();
 
             // Create the final output structure
             let output = JsonOutput {
@@ -1056,6 +2807,9 @@ fn output_results(public_api: &[ApiSymbol], args: &AnalyzeApiArgs) -> Result<()>
             );
             println!();
 
+            // Column width used to wrap docstrings below.
+            let wrap_width = effective_wrap_width(args);
+
             // Group by kind for better organization
             let mut by_kind: HashMap<&SymbolKind, Vec<&ApiSymbol>> = HashMap::new();
 
@@ -1088,12 +2842,23 @@ fn output_results(public_api: &[ApiSymbol], args: &AnalyzeApiArgs) -> Result<()>
                                 "private".red()
                             };
 
-                            println!(
-                                "  {} ({} external usages, {})",
-                                symbol.name.cyan().bold(),
-                                symbol.usage_count.to_string().green(),
-                                visibility
-                            );
+                            let breakdown = symbol.role_breakdown();
+                            if breakdown.is_empty() {
+                                println!(
+                                    "  {} ({} external usages, {})",
+                                    symbol.name.cyan().bold(),
+                                    symbol.usage_count.to_string().green(),
+                                    visibility
+                                );
+                            } else {
+                                println!(
+                                    "  {} ({} external usages [{}], {})",
+                                    symbol.name.cyan().bold(),
+                                    symbol.usage_count.to_string().green(),
+                                    breakdown,
+                                    visibility
+                                );
+                            }
 
                             // Print fully qualified name
                             println!(
@@ -1101,11 +2866,24 @@ fn output_results(public_api: &[ApiSymbol], args: &AnalyzeApiArgs) -> Result<()>
                                 symbol.definition.fully_qualified_name.cyan()
                             );
 
-                            // Print docstring if available
+                            // Note the re-export facade chain, when reached via one.
+                            if !symbol.reexport_path.is_empty() {
+                                println!(
+                                    "    Re-exported via: {}",
+                                    symbol.reexport_path.join(" -> ").cyan()
+                                );
+                            }
+
+                            // Print docstring if available, wrapped to fit.
                             if let Some(docstring) = &symbol.definition.docstring {
                                 let docstring = docstring.trim_matches('"').trim();
                                 if !docstring.is_empty() {
-                                    println!("    {}", docstring.italic());
+                                    let indent = "    ";
+                                    let body_width =
+                                        wrap_width.saturating_sub(indent.len()).max(1);
+                                    for line in optimal_wrap(docstring, body_width) {
+                                        println!("{}{}", indent, line.italic());
+                                    }
                                 }
                             }
 
@@ -1196,211 +2974,927 @@ fn detect_project_root(target_path: &Path) -> Result<PathBuf> {
     })
 }
 
-/// Check if a path is a Python file
-fn is_python_file(path: &Path) -> bool {
-    path.is_file()
-        && path.extension().map_or(false, |ext| {
-            ext.eq_ignore_ascii_case("py") || ext.eq_ignore_ascii_case("pyi")
-        })
+/// Check if a path is a Python file
+fn is_python_file(path: &Path) -> bool {
+    path.is_file()
+        && path.extension().map_or(false, |ext| {
+            ext.eq_ignore_ascii_case("py") || ext.eq_ignore_ascii_case("pyi")
+        })
+}
+
+/// Canonicalize a path, falling back to the path itself if the filesystem
+/// refuses (e.g. a broken symlink). Keeping a single canonical form per path
+/// means each file is canonicalized once rather than on every boundary check.
+fn canonicalize_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Whether the walk should skip descending into `entry`. We prune irrelevant
+/// directories (`__pycache__`, VCS and hidden directories) so that excluded
+/// subtrees are never walked at all, instead of walking everything and
+/// filtering afterwards.
+fn is_pruned_dir(entry: &walkdir::DirEntry) -> bool {
+    entry.depth() > 0
+        && entry.file_type().is_dir()
+        && entry.file_name().to_str().map_or(false, |name| {
+            name == "__pycache__" || name.starts_with('.')
+        })
+}
+
+/// Determine the target boundary of the target module/package as a set of
+/// canonical paths. Membership is then a constant-time `HashSet` lookup rather
+/// than an O(files × boundary) scan.
+fn determine_target_boundary(target_path: &Path) -> Result<HashSet<PathBuf>> {
+    debug!("Target path: {}", target_path.display());
+
+    let mut boundary = HashSet::new();
+
+    if target_path.is_file() {
+        if is_python_file(target_path) {
+            boundary.insert(canonicalize_path(target_path));
+        } else {
+            debug!("Not a Python file, skipping");
+        }
+    } else if target_path.is_dir() {
+        debug!("Target is a directory, scanning for Python files");
+        // Pattern-match while walking: prune uninteresting directories before
+        // descending rather than canonicalizing the whole tree up front.
+        let walker = WalkDir::new(target_path)
+            .into_iter()
+            .filter_entry(|entry| !is_pruned_dir(entry));
+        for entry in walker.filter_map(Result::ok) {
+            let path = entry.path();
+            if is_python_file(path) {
+                boundary.insert(canonicalize_path(path));
+            }
+        }
+    }
+
+    debug!("Found {} files in boundary", boundary.len());
+    Ok(boundary)
+}
+
+/// Check whether an already-canonicalized file path is within the target
+/// boundary. The caller canonicalizes once and reuses the result.
+fn is_file_within_target(boundary: &HashSet<PathBuf>, canonical_file_path: &Path) -> bool {
+    boundary.contains(canonical_file_path)
+}
+
+//------------------------------------------------------------------------------
+// Pluggable module resolution
+//------------------------------------------------------------------------------
+
+/// A module name resolved to a file on disk, along with whether it belongs to
+/// the target ("first-party") source tree or to a library search path.
+///
+/// Imports that resolve to a first-party location can be followed and attributed
+/// to the target; those found only on a search path are reachable but treated as
+/// external, and an import that resolves to neither is third-party and skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ResolvedImport {
+    /// The source file (`foo.py` or `foo/__init__.py`) or namespace-package
+    /// directory the module resolved to.
+    path: PathBuf,
+    /// Whether the module lives under one of the first-party source roots.
+    first_party: bool,
+}
+
+/// A strategy for turning a dotted module name into a location on disk.
+///
+/// Modeled on jrsonnet's `ImportResolver` and ethers-solc's
+/// `ProjectPathsConfig`: the analyzer depends only on this trait, so a project
+/// with a non-standard layout can supply its own resolution policy without the
+/// core having to special-case every directory convention.
+pub(crate) trait ModuleResolver {
+    /// Resolve `module_name`, as imported from `importer_path`, to a location on
+    /// disk. Returns `None` when the module is not part of any configured root
+    /// (treated as a third-party import and skipped rather than mis-attributed).
+    fn resolve_from(&self, importer_path: &Path, module_name: &str) -> Option<ResolvedImport>;
+}
+
+/// The default filesystem resolver: it tries each first-party source root, then
+/// each library search path (`site-packages`, namespace-package directories),
+/// applying import remappings before either.
+///
+/// A remapping rewrites a logical prefix to an actual directory — the
+/// `logical.prefix = ./actual/path` form ethers-solc uses — so a package that is
+/// imported under one name but lives elsewhere on disk still resolves.
+#[derive(Clone)]
+pub(crate) struct FileSystemResolver {
+    /// First-party roots, searched in order; a hit here is `first_party`.
+    source_roots: Vec<PathBuf>,
+    /// Library/search paths consulted after the source roots; a hit here is
+    /// reachable but external.
+    search_paths: Vec<PathBuf>,
+    /// `(logical prefix, target directory)` remappings, applied first.
+    remappings: Vec<(String, PathBuf)>,
+}
+
+impl FileSystemResolver {
+    /// Build a resolver for a project rooted at `project_root`.
+    ///
+    /// The project root and its `src/` subdirectory (when present) are always
+    /// first-party source roots; `extra_roots` are appended after them. Each
+    /// remapping string is parsed from the `logical.prefix = ./path` form and
+    /// silently dropped when malformed.
+    fn new(
+        project_root: &Path,
+        extra_roots: Vec<PathBuf>,
+        search_paths: Vec<PathBuf>,
+        remappings: &[String],
+    ) -> Self {
+        let mut source_roots = vec![project_root.to_path_buf()];
+        let src = project_root.join("src");
+        if src.is_dir() {
+            source_roots.push(src);
+        }
+        source_roots.extend(extra_roots);
+
+        Self {
+            source_roots,
+            search_paths,
+            remappings: remappings.iter().filter_map(|s| parse_remapping(s)).collect(),
+        }
+    }
+
+    /// The first-party source roots, used to seed project file discovery.
+    fn source_roots(&self) -> &[PathBuf] {
+        &self.source_roots
+    }
+}
+
+impl ModuleResolver for FileSystemResolver {
+    fn resolve_from(&self, _importer_path: &Path, module_name: &str) -> Option<ResolvedImport> {
+        // Remappings win over the plain roots so a relocated package resolves to
+        // its real home rather than a same-named directory under a source root.
+        for (prefix, target) in &self.remappings {
+            if let Some(rest) = strip_module_prefix(module_name, prefix) {
+                if let Some(path) = module_file_in(target, rest) {
+                    return Some(ResolvedImport { path, first_party: true });
+                }
+            }
+        }
+
+        for root in &self.source_roots {
+            if let Some(path) = module_file_in(root, module_name) {
+                return Some(ResolvedImport { path, first_party: true });
+            }
+        }
+
+        for root in &self.search_paths {
+            if let Some(path) = module_file_in(root, module_name) {
+                return Some(ResolvedImport { path, first_party: false });
+            }
+        }
+
+        None
+    }
+}
+
+/// Parse a `logical.prefix = ./actual/path` remapping, returning the prefix and
+/// its target directory, or `None` when the string has no `=`.
+fn parse_remapping(spec: &str) -> Option<(String, PathBuf)> {
+    let (prefix, path) = spec.split_once('=')?;
+    let prefix = prefix.trim();
+    let path = path.trim();
+    if prefix.is_empty() || path.is_empty() {
+        return None;
+    }
+    Some((prefix.to_string(), PathBuf::from(path)))
+}
+
+/// Strip a dotted module `prefix` from `module`, returning the remaining dotted
+/// path (empty when they are equal) or `None` when `module` is not under it.
+fn strip_module_prefix<'a>(module: &'a str, prefix: &str) -> Option<&'a str> {
+    if module == prefix {
+        return Some("");
+    }
+    module
+        .strip_prefix(prefix)
+        .and_then(|rest| rest.strip_prefix('.'))
+}
+
+/// Locate the file a dotted `module` maps to under `root`: a `module.py`, a
+/// `module/__init__.py`, or — for PEP 420 namespace packages — the directory
+/// itself. Returns `None` when none of those exist.
+fn module_file_in(root: &Path, module: &str) -> Option<PathBuf> {
+    let mut base = root.to_path_buf();
+    if !module.is_empty() {
+        for component in module.split('.') {
+            base.push(component);
+        }
+    }
+
+    let as_module = base.with_extension("py");
+    if as_module.is_file() {
+        return Some(as_module);
+    }
+
+    let as_package = base.join("__init__.py");
+    if as_package.is_file() {
+        return Some(as_package);
+    }
+
+    // A directory without `__init__.py` is a PEP 420 namespace package.
+    if base.is_dir() {
+        return Some(base);
+    }
+
+    None
+}
+
+//------------------------------------------------------------------------------
+// Layered configuration ([tool.pubscan])
+//------------------------------------------------------------------------------
+
+/// Configuration for pubscan, discovered from a `[tool.pubscan]` table in
+/// `pyproject.toml` (or a dedicated `pubscan.toml`). Every field is optional so
+/// that the file only overrides what it explicitly sets; CLI flags in turn
+/// override the file, which overrides built-in defaults.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub(crate) struct PubscanConfig {
+    /// Default output format.
+    output_format: Option<String>,
+
+    /// Path to the Python executable used for venv parsing.
+    python: Option<PathBuf>,
+
+    /// Whether to keep test files in the analysis.
+    no_ignore_test_files: Option<bool>,
+
+    /// Glob patterns identifying test files to exclude.
+    test_file_patterns: Vec<String>,
+
+    /// Symbols or modules that should always be treated as public even when
+    /// nothing in the repo imports them (e.g. plugin hooks, entry points).
+    always_public: Vec<String>,
+
+    /// Extra first-party source roots to resolve imports against (src-layout,
+    /// multi-package monorepos).
+    source_roots: Vec<PathBuf>,
+
+    /// Library/search paths (`site-packages`, namespace-package directories)
+    /// consulted after the source roots.
+    search_paths: Vec<PathBuf>,
+
+    /// Import remappings, each in the `logical.prefix = ./actual/path` form.
+    remappings: Vec<String>,
+
+    /// Directory names identifying test consumers (default: `tests`, `test`).
+    test_dirs: Vec<String>,
+
+    /// Directory names identifying example consumers (default: `examples`).
+    example_dirs: Vec<String>,
+
+    /// Directory names identifying benchmark consumers (default: `benches`).
+    bench_dirs: Vec<String>,
+}
+
+/// The `[tool]` table wrapper used when parsing `pyproject.toml`.
+#[derive(Debug, Default, Deserialize)]
+struct PyprojectTools {
+    #[serde(default)]
+    pubscan: PubscanConfig,
+}
+
+/// The top-level `pyproject.toml` shape we care about.
+#[derive(Debug, Default, Deserialize)]
+struct Pyproject {
+    #[serde(default)]
+    tool: PyprojectTools,
+}
+
+impl PubscanConfig {
+    /// Discover configuration by walking up from `project_root` looking for a
+    /// `pubscan.toml` or a `pyproject.toml` carrying a `[tool.pubscan]` table.
+    ///
+    /// Returns the default (empty) configuration when `isolated` is set or no
+    /// configuration file is found.
+    fn discover(project_root: &Path, isolated: bool) -> Self {
+        if isolated {
+            return Self::default();
+        }
+
+        let mut dir = Some(project_root);
+        while let Some(current) = dir {
+            // A dedicated pubscan.toml is a whole-file [tool.pubscan]-equivalent.
+            let pubscan_toml = current.join("pubscan.toml");
+            if let Ok(contents) = fs::read_to_string(&pubscan_toml) {
+                match toml::from_str::<PubscanConfig>(&contents) {
+                    Ok(config) => {
+                        debug!("Loaded config from {}", pubscan_toml.display());
+                        return config;
+                    }
+                    Err(e) => debug!("Failed to parse {}: {}", pubscan_toml.display(), e),
+                }
+            }
+
+            let pyproject = current.join("pyproject.toml");
+            if let Ok(contents) = fs::read_to_string(&pyproject) {
+                match toml::from_str::<Pyproject>(&contents) {
+                    Ok(parsed) => {
+                        debug!("Loaded [tool.pubscan] from {}", pyproject.display());
+                        return parsed.tool.pubscan;
+                    }
+                    Err(e) => debug!("Failed to parse {}: {}", pyproject.display(), e),
+                }
+            }
+
+            dir = current.parent();
+        }
+
+        Self::default()
+    }
+
+    /// Whether a symbol (by bare name or fully qualified name) is on the
+    /// always-public allowlist.
+    fn is_always_public(&self, symbol: &ApiSymbol) -> bool {
+        self.always_public
+            .iter()
+            .any(|entry| entry == &symbol.name || entry == &symbol.definition.fully_qualified_name)
+    }
+}
+
+/// Locate the `site-packages` directory of the venv a `python` executable
+/// belongs to, by convention rather than by invoking it: `<venv>/lib/pythonX.Y/
+/// site-packages` on Unix, `<venv>/Lib/site-packages` on Windows. Returns
+/// `None` when the executable's venv layout doesn't match either convention.
+fn site_packages_from_python(python: &Path) -> Option<PathBuf> {
+    let venv_root = python.parent()?.parent()?;
+
+    let windows = venv_root.join("Lib").join("site-packages");
+    if windows.is_dir() {
+        return Some(windows);
+    }
+
+    let lib = venv_root.join("lib");
+    let mut entries: Vec<PathBuf> = fs::read_dir(&lib)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_dir()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("python"))
+        })
+        .collect();
+    entries.sort();
+
+    entries.into_iter().find_map(|dir| {
+        let site_packages = dir.join("site-packages");
+        site_packages.is_dir().then_some(site_packages)
+    })
+}
+
+/// Whether `name` matches any of `patterns`, each a simple `*`-wildcard glob
+/// (e.g. `*_test.py`, `test_*.py`). An empty pattern list never matches.
+fn matches_any_pattern(name: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|pattern| matches_glob(name, pattern))
+}
+
+/// Match `name` against a single `*`-wildcard `pattern`: `*` stands for any
+/// (possibly empty) run of characters, with no other special characters.
+fn matches_glob(name: &str, pattern: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return name == pattern;
+    }
+
+    let mut rest = name;
+
+    if let Some(first) = parts.first().filter(|s| !s.is_empty()) {
+        let Some(after) = rest.strip_prefix(first) else {
+            return false;
+        };
+        rest = after;
+    }
+
+    if let Some(last) = parts.last().filter(|s| !s.is_empty()) {
+        let Some(before) = rest.strip_suffix(last) else {
+            return false;
+        };
+        rest = before;
+    }
+
+    for middle in parts[1..parts.len() - 1].iter().filter(|s| !s.is_empty()) {
+        match rest.find(middle) {
+            Some(pos) => rest = &rest[pos + middle.len()..],
+            None => return false,
+        }
+    }
+
+    true
+}
+
+//------------------------------------------------------------------------------
+// Incremental analysis cache
+//------------------------------------------------------------------------------
+
+/// The filename used for the on-disk fact cache within the cache directory.
+const CACHE_FILE_NAME: &str = "pubscan-cache.json";
+
+/// A cheap content fingerprint used to decide whether a file's cached facts are
+/// still valid. Combines the file size, modification time, and a content hash so
+/// that neither a touch-without-edit nor an edit-without-size-change slips past.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct FileFingerprint {
+    size: u64,
+    mtime_ns: u128,
+    content_hash: u64,
+}
+
+impl FileFingerprint {
+    /// Compute the fingerprint of a file on disk.
+    fn compute(path: &Path) -> Result<Self> {
+        use std::hash::{Hash, Hasher};
+
+        let metadata = fs::metadata(path)?;
+        let mtime_ns = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos());
+
+        let contents = fs::read(path)?;
+        // A content hash keeps us honest when mtime is unreliable (e.g. across
+        // checkouts). blake3/sha would be stronger, but the std hasher avoids a
+        // new dependency and collisions here only cost a spurious cache miss.
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        contents.hash(&mut hasher);
+
+        Ok(Self {
+            size: metadata.len(),
+            mtime_ns,
+            content_hash: hasher.finish(),
+        })
+    }
+}
+
+/// A single cached file's extracted facts, keyed by its fingerprint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CacheEntry {
+    fingerprint: FileFingerprint,
+    defined: Vec<(String, DefinedSymbol)>,
+}
+
+/// A persistent, content-addressed cache of per-file analysis facts.
+///
+/// Only per-file *facts* (a file's own definitions) are cached — never the
+/// global usage tally, which must always be recomputed from every file's
+/// reference set because "effective public API" depends on consumers.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct FactCache {
+    entries: HashMap<PathBuf, CacheEntry>,
+
+    #[serde(skip)]
+    location: PathBuf,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl FactCache {
+    /// Load the cache from `cache_dir`, returning an empty cache if none exists
+    /// yet or it cannot be parsed (a stale/corrupt cache is never fatal).
+    fn load(cache_dir: &Path) -> Self {
+        let location = cache_dir.join(CACHE_FILE_NAME);
+        let mut cache = fs::read_to_string(&location)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<FactCache>(&contents).ok())
+            .unwrap_or_default();
+        cache.location = location;
+        cache
+    }
+
+    /// Return the cached facts for `path` if its fingerprint is unchanged.
+    fn get_fresh(
+        &self,
+        path: &Path,
+        fingerprint: &FileFingerprint,
+    ) -> Option<Vec<(String, DefinedSymbol)>> {
+        self.entries.get(path).and_then(|entry| {
+            (&entry.fingerprint == fingerprint).then(|| entry.defined.clone())
+        })
+    }
+
+    /// Record fresh facts for a file.
+    fn insert(
+        &mut self,
+        path: PathBuf,
+        fingerprint: FileFingerprint,
+        defined: Vec<(String, DefinedSymbol)>,
+    ) {
+        self.entries.insert(
+            path,
+            CacheEntry {
+                fingerprint,
+                defined,
+            },
+        );
+        self.dirty = true;
+    }
+
+    /// Persist the cache to disk if it changed since it was loaded.
+    fn save(&self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(parent) = self.location.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string(self)?;
+        fs::write(&self.location, json)?;
+        Ok(())
+    }
+}
+
+/// Extract candidate symbols from the target files using SemanticModel.
+///
+/// When a [`FactCache`] is supplied, per-file definitions for files whose
+/// fingerprint is unchanged are reused instead of re-parsing. The global
+/// candidate map is always rebuilt from the union of cached and fresh facts;
+/// the cache only ever stores per-file *facts*, never the final verdict.
+fn extract_candidate_symbols(
+    target_files: &[(PathBuf, ResolvedFile)],
+    _resolver: &Resolver,
+    cache: Option<&mut FactCache>,
+) -> Result<HashMap<String, DefinedSymbol>> {
+    let mut candidates = HashMap::new();
+
+    // Collect per-file facts, reusing the cache where the fingerprint matches.
+    let mut cache = cache;
+    for (path, resolved_file) in target_files {
+        let source_path = resolved_file.path();
+        let fingerprint = FileFingerprint::compute(source_path).ok();
+
+        // Try the cache first.
+        if let (Some(cache), Some(fingerprint)) = (cache.as_deref_mut(), fingerprint.as_ref()) {
+            if let Some(facts) = cache.get_fresh(path, fingerprint) {
+                trace!("Cache hit for {}", path.display());
+                for (name, symbol) in facts {
+                    candidates.insert(name.clone(), symbol.clone());
+                }
+                continue;
+            }
+        }
+
+        // Cache miss (or caching disabled): parse and extract fresh facts.
+        let file_content = std::fs::read_to_string(source_path)?;
+        let facts = extract_file_symbols(path, &file_content);
+
+        if let (Some(cache), Some(fingerprint)) = (cache.as_deref_mut(), fingerprint) {
+            cache.insert(path.clone(), fingerprint, facts.clone());
+        }
+
+        for (name, symbol) in facts {
+            candidates.insert(name, symbol);
+        }
+    }
+
+    Ok(candidates)
+}
+
+/// The names in a module that refer to `typing` and to `TYPE_CHECKING`, used to
+/// recognize `if TYPE_CHECKING:` guards regardless of how they were imported.
+#[derive(Debug, Default)]
+struct TypingContext {
+    /// Local names bound to the `typing` module (`import typing`,
+    /// `import typing as t`).
+    modules: HashSet<String>,
+    /// Local names bound to `typing.TYPE_CHECKING`
+    /// (`from typing import TYPE_CHECKING [as tc]`).
+    flags: HashSet<String>,
+}
+
+impl TypingContext {
+    /// Scan a module body for the imports that introduce `typing` /
+    /// `TYPE_CHECKING` names.
+    fn collect(body: &[ast::Stmt]) -> Self {
+        let mut ctx = TypingContext::default();
+        for stmt in body {
+            match stmt {
+                ast::Stmt::Import(import) => {
+                    for alias in &import.names {
+                        if alias.name.as_str() == "typing" {
+                            let local = alias
+                                .asname
+                                .as_ref()
+                                .map_or("typing", |name| name.as_str());
+                            ctx.modules.insert(local.to_string());
+                        }
+                    }
+                }
+                ast::Stmt::ImportFrom(import) => {
+                    let is_typing =
+                        import.module.as_ref().map_or(false, |m| m.as_str() == "typing");
+                    if is_typing {
+                        for alias in &import.names {
+                            if alias.name.as_str() == "TYPE_CHECKING" {
+                                let local = alias
+                                    .asname
+                                    .as_ref()
+                                    .map_or("TYPE_CHECKING", |name| name.as_str());
+                                ctx.flags.insert(local.to_string());
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        ctx
+    }
+
+    /// Whether an `if` test is a `TYPE_CHECKING` guard: the bare flag name or a
+    /// `typing.TYPE_CHECKING` attribute access.
+    fn is_type_checking_test(&self, test: &ast::Expr) -> bool {
+        match test {
+            ast::Expr::Name(name) => self.flags.contains(name.id.as_str()),
+            ast::Expr::Attribute(attr) => {
+                attr.attr.as_str() == "TYPE_CHECKING"
+                    && matches!(attr.value.as_ref(), ast::Expr::Name(base) if self.modules.contains(base.id.as_str()))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Whether an assignment targets the `__all__` export list.
+fn assign_targets_all(assign: &ast::StmtAssign) -> bool {
+    assign
+        .targets
+        .iter()
+        .any(|target| matches!(target, ast::Expr::Name(name) if name.id.as_str() == "__all__"))
+}
+
+/// Record the symbol(s) a single statement defines into `candidates`.
+///
+/// Shared by the top-level pass and the `if TYPE_CHECKING:` recursion; the
+/// latter passes `type_checking_only = true` so the defined names are tagged as
+/// type-only. `__all__` targets are skipped here — they are handled as exports.
+fn collect_definition(
+    candidates: &mut HashMap<String, DefinedSymbol>,
+    stmt: &ast::Stmt,
+    module_name: &str,
+    path: &Path,
+    type_checking_only: bool,
+) {
+    match stmt {
+        ast::Stmt::ClassDef(class_def) => {
+            let name = class_def.name.as_str();
+            let is_private =
+                name.starts_with('_') && !name.starts_with("__") && !name.ends_with("__");
+            let docstring = extract_docstring_from_body(&class_def.body);
+
+            candidates.insert(
+                name.to_string(),
+                DefinedSymbol {
+                    kind: SymbolKind::Class,
+                    location: path.to_path_buf(),
+                    docstring,
+                    is_public: !is_private,
+                    fully_qualified_name: format!("{}.{}", module_name, name),
+                    type_checking_only,
+                },
+            );
+        }
+        ast::Stmt::FunctionDef(func_def) => {
+            let name = func_def.name.as_str();
+            let is_private =
+                name.starts_with('_') && !name.starts_with("__") && !name.ends_with("__");
+            let docstring = extract_docstring_from_body(&func_def.body);
+
+            candidates.insert(
+                name.to_string(),
+                DefinedSymbol {
+                    kind: SymbolKind::Function,
+                    location: path.to_path_buf(),
+                    docstring,
+                    is_public: !is_private,
+                    fully_qualified_name: format!("{}.{}", module_name, name),
+                    type_checking_only,
+                },
+            );
+        }
+        ast::Stmt::Assign(assign) => {
+            for target in &assign.targets {
+                if let ast::Expr::Name(name) = target {
+                    let id = name.id.as_str();
+                    if id == "__all__" {
+                        continue;
+                    }
+                    let is_private =
+                        id.starts_with('_') && !id.starts_with("__") && !id.ends_with("__");
+                    candidates.insert(
+                        id.to_string(),
+                        DefinedSymbol {
+                            kind: SymbolKind::Variable,
+                            location: path.to_path_buf(),
+                            docstring: None,
+                            is_public: !is_private,
+                            fully_qualified_name: format!("{}.{}", module_name, id),
+                            type_checking_only,
+                        },
+                    );
+                }
+            }
+        }
+        // Annotated globals: `X: int = 5`, bare `X: int`, `TIMEOUT: Final = 30`,
+        // and the legacy `Alias: TypeAlias = ...` form. The annotation selects
+        // the kind; `Final`/`ClassVar` mark a constant, `TypeAlias` a type alias.
+        ast::Stmt::AnnAssign(ann_assign) => {
+            if let ast::Expr::Name(name) = ann_assign.target.as_ref() {
+                let id = name.id.as_str();
+                let is_private =
+                    id.starts_with('_') && !id.starts_with("__") && !id.ends_with("__");
+                let kind = match annotation_head(&ann_assign.annotation) {
+                    Some("Final" | "ClassVar") => SymbolKind::Constant,
+                    Some("TypeAlias") => SymbolKind::TypeAlias,
+                    _ => SymbolKind::Variable,
+                };
+                candidates.insert(
+                    id.to_string(),
+                    DefinedSymbol {
+                        kind,
+                        location: path.to_path_buf(),
+                        docstring: None,
+                        is_public: !is_private,
+                        fully_qualified_name: format!("{}.{}", module_name, id),
+                        type_checking_only,
+                    },
+                );
+            }
+        }
+        // PEP 695 `type Alias = ...` statements.
+        ast::Stmt::TypeAlias(type_alias) => {
+            if let ast::Expr::Name(name) = type_alias.name.as_ref() {
+                let id = name.id.as_str();
+                let is_private =
+                    id.starts_with('_') && !id.starts_with("__") && !id.ends_with("__");
+                candidates.insert(
+                    id.to_string(),
+                    DefinedSymbol {
+                        kind: SymbolKind::TypeAlias,
+                        location: path.to_path_buf(),
+                        docstring: None,
+                        is_public: !is_private,
+                        fully_qualified_name: format!("{}.{}", module_name, id),
+                        type_checking_only,
+                    },
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The trailing name of a type annotation expression, ignoring any module
+/// qualifier and subscript: `Final`, `typing.Final`, and `Final[int]` all yield
+/// `"Final"`. Returns `None` for annotations that are not a (possibly qualified
+/// or subscripted) name.
+fn annotation_head(annotation: &ast::Expr) -> Option<&str> {
+    match annotation {
+        ast::Expr::Name(name) => Some(name.id.as_str()),
+        ast::Expr::Attribute(attr) => Some(attr.attr.as_str()),
+        ast::Expr::Subscript(subscript) => annotation_head(&subscript.value),
+        _ => None,
+    }
 }
 
-/// Determine the target boundary of the target module/package
-fn determine_target_boundary(target_path: &Path) -> Result<Vec<PathBuf>> {
-    debug!("Target path: {}", target_path.display());
+/// Extract the top-level defined symbols of a single Python source file.
+///
+/// This is the unit of work cached by [`FactCache`]: it depends only on the
+/// file's own contents, never on other files.
+fn extract_file_symbols(path: &Path, file_content: &str) -> Vec<(String, DefinedSymbol)> {
+    let mut candidates: HashMap<String, DefinedSymbol> = HashMap::new();
 
-    let mut boundary = Vec::new();
+    // The module's explicit export list, accumulated across every `__all__`
+    // statement (assignment, augmentation, and `.extend`/`.append` calls).
+    let mut exports = ExportList::default();
 
-    if target_path.is_file() {
-        debug!("Target is a file, adding to boundary");
-        let normalized_path = match fs::canonicalize(target_path) {
-            Ok(path) => path,
-            Err(e) => {
-                debug!("Error normalizing path {}: {}", target_path.display(), e);
-                target_path.to_path_buf()
-            }
-        };
-        trace!("Normalized path: {}", normalized_path.display());
+    if let Ok(parsed) = ruff_python_parser::parse_module(file_content) {
+        // Get module name from the file path for qualified names
+        let module_name = get_module_name_from_path(path);
 
-        // Check if it's a Python file
-        if is_python_file(target_path) {
-            boundary.push(normalized_path);
-        } else {
-            debug!("Not a Python file, skipping");
-        }
-    } else if target_path.is_dir() {
-        debug!("Target is a directory, scanning for Python files");
-        // Recursively find all Python files in the directory
-        for entry in WalkDir::new(target_path).into_iter().filter_map(Result::ok) {
-            let path = entry.path();
-            trace!("Checking path: {}", path.display());
-            if is_python_file(path) {
-                trace!("Adding Python file to boundary: {}", path.display());
-                match fs::canonicalize(path) {
-                    Ok(canonical_path) => boundary.push(canonical_path),
-                    Err(e) => {
-                        debug!("Error normalizing path {}: {}", path.display(), e);
-                        boundary.push(path.to_path_buf());
+        // Track the names that refer to `typing` and to `TYPE_CHECKING` so we
+        // can recognize `if TYPE_CHECKING:` guards below.
+        let typing = TypingContext::collect(&parsed.syntax().body);
+
+        // Process the top-level names
+        for stmt in &parsed.syntax().body {
+            match stmt {
+                // `__all__` contributions: collect the listed names, then let the
+                // definition pass handle any ordinary variable targets.
+                ast::Stmt::Assign(assign) => {
+                    if assign_targets_all(assign) {
+                        exports.collect_from_value(&assign.value);
                     }
+                    collect_definition(&mut candidates, stmt, &module_name, path, false);
                 }
+                // `__all__ += [...]` augments the export list in place.
+                ast::Stmt::AugAssign(aug_assign) => {
+                    if let ast::Expr::Name(name) = aug_assign.target.as_ref() {
+                        if name.id.as_str() == "__all__" {
+                            exports.collect_from_value(&aug_assign.value);
+                        }
+                    }
+                }
+                // `__all__.extend([...])` / `__all__.append("x")`.
+                ast::Stmt::Expr(expr_stmt) => {
+                    if let ast::Expr::Call(call) = expr_stmt.value.as_ref() {
+                        exports.collect_from_call(call);
+                    }
+                }
+                // Symbols guarded by `if TYPE_CHECKING:` exist only for the type
+                // checker: recurse into the guarded body and tag what it defines
+                // as type-checking-only so reporting can count it separately.
+                ast::Stmt::If(if_stmt) if typing.is_type_checking_test(&if_stmt.test) => {
+                    for inner in &if_stmt.body {
+                        collect_definition(&mut candidates, inner, &module_name, path, true);
+                    }
+                }
+                other => collect_definition(&mut candidates, other, &module_name, path, false),
             }
         }
     }
 
-    debug!("Found {} files in boundary", boundary.len());
-    Ok(boundary)
-}
+    // Standard Python contract: once a module declares `__all__`, only the
+    // names it lists are public. Apply this as a second pass now that the whole
+    // export set is known, since `__all__` may precede or follow the
+    // definitions it names.
+    if exports.defined {
+        let listed: HashSet<&str> = exports.names.iter().map(String::as_str).collect();
+        for (name, symbol) in &mut candidates {
+            symbol.is_public = listed.contains(name.as_str());
+        }
+    }
 
-/// Check whether a file is within the target boundary, using canonical paths for comparison.
-fn is_file_within_target(boundary: &[PathBuf], file_path: &Path) -> bool {
-    // Get canonical path for the file being checked
-    let canonical_file_path =
-        fs::canonicalize(file_path).unwrap_or_else(|_| file_path.to_path_buf());
+    candidates.into_iter().collect::<Vec<_>>()
+}
 
-    trace!(
-        "Checking if {} is in boundary",
-        canonical_file_path.display()
-    );
+/// The set of names a module exports via `__all__`, accumulated across every
+/// statement that contributes to it. `defined` records whether the module
+/// declared `__all__` at all — the trigger for export-list-as-privacy.
+#[derive(Debug, Default)]
+struct ExportList {
+    defined: bool,
+    names: Vec<String>,
+}
 
-    if boundary.len() == 1 {
-        // Single file case
-        let canonical_boundary_path = &boundary[0];
-        let result = canonical_file_path == *canonical_boundary_path;
-        trace!(
-            "Single file comparison: {} == {} ? {}",
-            canonical_file_path.display(),
-            canonical_boundary_path.display(),
-            result
-        );
-        result
-    } else {
-        // Multiple files case
-        for boundary_path in boundary {
-            if &canonical_file_path == boundary_path {
-                trace!(
-                    "Multi-file match: {} == {}",
-                    canonical_file_path.display(),
-                    boundary_path.display()
-                );
-                return true;
+impl ExportList {
+    /// Collect names from an `__all__` value expression: a list or tuple of
+    /// string literals, or a concatenation (`base + ["x"]`) of such.
+    fn collect_from_value(&mut self, value: &ast::Expr) {
+        self.defined = true;
+        match value {
+            ast::Expr::List(list) => self.push_string_elements(&list.elts),
+            ast::Expr::Tuple(tuple) => self.push_string_elements(&tuple.elts),
+            ast::Expr::BinOp(binop) if binop.op == ast::Operator::Add => {
+                self.collect_from_value(&binop.left);
+                self.collect_from_value(&binop.right);
             }
+            _ => {}
         }
-        false
     }
-}
-
-/// Extract candidate symbols from the target files using SemanticModel
-fn extract_candidate_symbols(
-    target_files: &[(PathBuf, ResolvedFile)],
-    _resolver: &Resolver,
-) -> Result<HashMap<String, DefinedSymbol>> {
-    let mut candidates = HashMap::new();
-    let _typing_modules: Vec<String> = Vec::new(); // Empty list for typing modules
-
-    for (path, resolved_file) in target_files {
-        // Read and parse the file content
-        let file_content = std::fs::read_to_string(resolved_file.path())?;
-        let parsed = ruff_python_parser::parse_module(&file_content);
 
-        if let Ok(parsed) = parsed {
-            // Get module name from the file path for qualified names
-            let module_name = get_module_name_from_path(path);
+    /// Collect names from an `__all__.extend([...])` or `__all__.append("x")`
+    /// call; other calls are ignored.
+    fn collect_from_call(&mut self, call: &ast::ExprCall) {
+        let ast::Expr::Attribute(attr) = call.func.as_ref() else {
+            return;
+        };
+        let ast::Expr::Name(name) = attr.value.as_ref() else {
+            return;
+        };
+        if name.id.as_str() != "__all__" {
+            return;
+        }
 
-            // Process the top-level names
-            for stmt in &parsed.syntax().body {
-                match stmt {
-                    ast::Stmt::ClassDef(class_def) => {
-                        // Process class definition
-                        let name = class_def.name.as_str();
-                        let is_private = name.starts_with('_')
-                            && !name.starts_with("__")
-                            && !name.ends_with("__");
-                        let docstring = extract_docstring_from_body(&class_def.body);
-
-                        // Construct fully qualified name directly
-                        let mut fully_qualified_name = module_name.clone();
-                        fully_qualified_name.push('.');
-                        fully_qualified_name.push_str(name);
-
-                        candidates.insert(
-                            name.to_string(),
-                            DefinedSymbol {
-                                kind: SymbolKind::Class,
-                                location: path.clone(),
-                                docstring,
-                                is_public: !is_private,
-                                fully_qualified_name,
-                            },
-                        );
-                    }
-                    ast::Stmt::FunctionDef(func_def) => {
-                        // Process function definition
-                        let name = func_def.name.as_str();
-                        let is_private = name.starts_with('_')
-                            && !name.starts_with("__")
-                            && !name.ends_with("__");
-                        let docstring = extract_docstring_from_body(&func_def.body);
-
-                        // Construct fully qualified name directly
-                        let mut fully_qualified_name = module_name.clone();
-                        fully_qualified_name.push('.');
-                        fully_qualified_name.push_str(name);
-
-                        candidates.insert(
-                            name.to_string(),
-                            DefinedSymbol {
-                                kind: SymbolKind::Function,
-                                location: path.clone(),
-                                docstring,
-                                is_public: !is_private,
-                                fully_qualified_name,
-                            },
-                        );
-                    }
-                    ast::Stmt::Assign(assign) => {
-                        // Process variable assignments
-                        for target in &assign.targets {
-                            if let ast::Expr::Name(name) = target {
-                                let id = name.id.as_str();
-                                let is_private = id.starts_with('_')
-                                    && !id.starts_with("__")
-                                    && !id.ends_with("__");
-                                let fully_qualified_name = format!("{}.{}", module_name, id);
-
-                                // Check if this is an __all__ definition
-                                if id == "__all__" {
-                                    if let ast::Expr::List(list) = &assign.value.as_ref() {
-                                        for elt in &list.elts {
-                                            if let ast::Expr::StringLiteral(string_lit) = elt {
-                                                let value = string_lit.value.to_str();
-                                                // Mark items in __all__ as public
-                                                if let Some(symbol) = candidates.get_mut(value) {
-                                                    symbol.is_public = true;
-                                                }
-                                            }
-                                        }
-                                    }
-                                } else {
-                                    candidates.insert(
-                                        id.to_string(),
-                                        DefinedSymbol {
-                                            kind: SymbolKind::Variable,
-                                            location: path.clone(),
-                                            docstring: None,
-                                            is_public: !is_private,
-                                            fully_qualified_name,
-                                        },
-                                    );
-                                }
-                            }
-                        }
-                    }
-                    _ => {}
+        match attr.attr.as_str() {
+            "extend" => {
+                self.defined = true;
+                for arg in &call.arguments.args {
+                    self.collect_from_value(arg);
                 }
             }
+            "append" => {
+                self.defined = true;
+                self.push_string_elements(&call.arguments.args);
+            }
+            _ => {}
         }
     }
 
-    Ok(candidates)
+    /// Push the string-literal values of `elements`, ignoring non-literals.
+    fn push_string_elements(&mut self, elements: &[ast::Expr]) {
+        for element in elements {
+            if let ast::Expr::StringLiteral(string_lit) = element {
+                self.names.push(string_lit.value.to_str().to_string());
+            }
+        }
+    }
 }
 
 /// Helper function to extract a module name from a file path
@@ -1408,6 +3902,13 @@ fn get_module_name_from_path(path: &Path) -> String {
     // Get the canonical path if possible to avoid relative path issues
     let canonical_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
 
+    // Name the file relative to its project's resolved source root, which gives
+    // correct results for `src/`-layout and PEP 420 namespace packages. The
+    // `__init__.py` walk remains the fallback when no source root is detected.
+    if let Some(name) = ProjectModel::discover(&canonical_path).module_name(&canonical_path) {
+        return name;
+    }
+
     // Extract filename without extension
     let file_stem = canonical_path
         .file_stem()
@@ -1439,6 +3940,144 @@ fn get_module_name_from_path(path: &Path) -> String {
     file_stem.to_string()
 }
 
+/// A minimal project model: the source roots a package's modules are named
+/// relative to. Analogous to rust-analyzer's `project_model`, it answers one
+/// question — given a file, what dotted module name should it have — without
+/// relying on an unbroken chain of `__init__.py` files, so it stays correct for
+/// `src/`-layout projects and PEP 420 namespace packages.
+#[derive(Clone)]
+struct ProjectModel {
+    /// Candidate source roots, longest path first so the most specific wins.
+    source_roots: Vec<PathBuf>,
+}
+
+impl ProjectModel {
+    /// Discover the source roots for the project containing `anchor`,
+    /// caching the result per directory walked.
+    ///
+    /// `get_module_name_from_path` calls this once per file, so without a
+    /// cache a large project re-walks the filesystem and re-parses its
+    /// `pyproject.toml` from scratch for every file it names. Every directory
+    /// visited on the way up is recorded, so later discoveries from a sibling
+    /// file short-circuit as soon as they reach one of them.
+    fn discover(anchor: &Path) -> Self {
+        static CACHE: OnceLock<Mutex<HashMap<PathBuf, ProjectModel>>> = OnceLock::new();
+        let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+
+        let mut visited = Vec::new();
+        let mut current = anchor.parent();
+        while let Some(dir) = current {
+            if let Some(model) = cache.lock().unwrap().get(dir) {
+                let model = model.clone();
+                cache
+                    .lock()
+                    .unwrap()
+                    .extend(visited.into_iter().map(|dir| (dir, model.clone())));
+                return model;
+            }
+            visited.push(dir.to_path_buf());
+
+            let pyproject = dir.join("pyproject.toml");
+            if pyproject.is_file() || dir.join("setup.py").is_file() {
+                let model = Self::discovered_at(dir, &pyproject);
+                cache
+                    .lock()
+                    .unwrap()
+                    .extend(visited.into_iter().map(|dir| (dir, model.clone())));
+                return model;
+            }
+            current = dir.parent();
+        }
+
+        let model = Self { source_roots: Vec::new() };
+        cache
+            .lock()
+            .unwrap()
+            .extend(visited.into_iter().map(|dir| (dir, model.clone())));
+        model
+    }
+
+    /// Build the model once a project root `dir` (declaring `pyproject`) has
+    /// been located: the conventional `src/` directory and any
+    /// `[tool.setuptools] package-dir` roots, plus `dir` itself, most specific
+    /// (longest) root first.
+    fn discovered_at(dir: &Path, pyproject: &Path) -> Self {
+        let mut source_roots = Vec::new();
+        for rel in package_dir_roots(pyproject) {
+            let root = dir.join(rel);
+            if root.is_dir() {
+                source_roots.push(root);
+            }
+        }
+        let src = dir.join("src");
+        if src.is_dir() {
+            source_roots.push(src);
+        }
+        source_roots.push(dir.to_path_buf());
+
+        source_roots.sort_by_key(|root| std::cmp::Reverse(root.components().count()));
+        source_roots.dedup();
+        Self { source_roots }
+    }
+
+    /// Dotted module name of `path` relative to the source root that contains
+    /// it, or `None` when no declared source root applies (caller falls back to
+    /// the `__init__.py` walk). `__init__.py` names the package directory.
+    fn module_name(&self, path: &Path) -> Option<String> {
+        for root in &self.source_roots {
+            let Ok(rel) = path.strip_prefix(root) else {
+                continue;
+            };
+
+            let mut components: Vec<String> = rel
+                .components()
+                .filter_map(|c| c.as_os_str().to_str().map(str::to_string))
+                .collect();
+
+            // Drop the trailing file component, keeping the directory path for
+            // `__init__.py` and appending the module stem otherwise.
+            let file = components.pop()?;
+            let stem = Path::new(&file).file_stem().and_then(|s| s.to_str())?;
+            if stem != "__init__" {
+                components.push(stem.to_string());
+            }
+
+            if components.is_empty() {
+                return None;
+            }
+            return Some(components.join("."));
+        }
+        None
+    }
+}
+
+/// Parse the `package-dir` roots declared in a `pyproject.toml`, mapping the
+/// setuptools `[tool.setuptools] package-dir = { "" = "src" }` form to the
+/// directories packages are rooted at. Returns an empty list when the file is
+/// absent or declares no `package-dir`.
+fn package_dir_roots(pyproject: &Path) -> Vec<String> {
+    let Ok(contents) = fs::read_to_string(pyproject) else {
+        return Vec::new();
+    };
+    let Ok(value) = contents.parse::<toml::Value>() else {
+        return Vec::new();
+    };
+
+    value
+        .get("tool")
+        .and_then(|t| t.get("setuptools"))
+        .and_then(|s| s.get("package-dir"))
+        .and_then(toml::Value::as_table)
+        .map(|table| {
+            table
+                .values()
+                .filter_map(toml::Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// Helper function to walk up directory tree and collect package components
 fn get_package_components(start_dir: &Path) -> Vec<String> {
     let mut components = Vec::new();
@@ -1581,6 +4220,528 @@ mod tests {
         Ok(())
     }
 
+    fn snap(module: &str, symbol: &str, kind: &str) -> SymbolSnapshot {
+        SymbolSnapshot {
+            module: module.to_string(),
+            symbol: symbol.to_string(),
+            kind: kind.to_string(),
+            usage_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_diff_snapshots_classifies_changes() {
+        let baseline = vec![
+            snap("pkg.core", "add", "function"),
+            snap("pkg.core", "subtract", "function"),
+            snap("pkg.models", "User", "class"),
+        ];
+        let current = vec![
+            snap("pkg.core", "add", "function"),
+            snap("pkg.models", "User", "function"), // kind changed
+            snap("pkg.core", "divide", "function"), // added
+        ];
+
+        let changes = diff_snapshots(&baseline, &current);
+
+        assert!(changes.contains(&SymbolChange::Removed(snap("pkg.core", "subtract", "function"))));
+        assert!(changes.iter().any(|c| matches!(c, SymbolChange::Added(s) if s.symbol == "divide")));
+        assert!(changes
+            .iter()
+            .any(|c| matches!(c, SymbolChange::Changed { new, .. } if new.symbol == "User")));
+    }
+
+    #[test]
+    fn test_unified_line_diff_is_deterministic() {
+        let old = vec!["a::x".to_string(), "a::y".to_string()];
+        let new = vec!["a::x".to_string(), "a::z".to_string()];
+        let diff = unified_line_diff(&old, &new);
+        assert_eq!(diff, "  a::x\n- a::y\n+ a::z\n");
+    }
+
+    #[test]
+    fn test_resolve_from_module_handles_relative_levels() {
+        let parse = |src: &str| {
+            let parsed = ruff_python_parser::parse_module(src).unwrap();
+            match &parsed.syntax().body[0] {
+                ast::Stmt::ImportFrom(import_from) => import_from.clone(),
+                _ => panic!("expected an import-from statement"),
+            }
+        };
+
+        // `from .core import add` inside package `mypkg`.
+        let stmt = parse("from .core import add");
+        assert_eq!(resolve_from_module(&stmt, "mypkg").as_deref(), Some("mypkg.core"));
+
+        // `from ..other import x` inside package `mypkg.sub`.
+        let stmt = parse("from ..other import x");
+        assert_eq!(
+            resolve_from_module(&stmt, "mypkg.sub").as_deref(),
+            Some("mypkg.other")
+        );
+
+        // `from . import submodule` inside package `mypkg`.
+        let stmt = parse("from . import submodule");
+        assert_eq!(resolve_from_module(&stmt, "mypkg").as_deref(), Some("mypkg"));
+
+        // Walking above the root is unresolved.
+        let stmt = parse("from ... import x");
+        assert_eq!(resolve_from_module(&stmt, "mypkg"), None);
+    }
+
+    #[test]
+    fn test_xml_escape_handles_predefined_entities() {
+        assert_eq!(
+            xml_escape(r#"<a href="x">&'y'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&apos;y&apos;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_optimal_wrap_prefers_balanced_lines() {
+        // Greedy first-fit fills "aaa bb" exactly, then strands "cc" alone before
+        // "ddddd"; optimal fit moves "bb" down to balance the slack instead.
+        let lines = optimal_wrap("aaa bb cc ddddd", 6);
+        assert_eq!(lines, vec!["aaa", "bb cc", "ddddd"]);
+
+        // A wide (CJK) character counts as two columns, so only one fits.
+        let lines = optimal_wrap("\u{4e2d} \u{6587}", 2);
+        assert_eq!(lines, vec!["\u{4e2d}", "\u{6587}"]);
+    }
+
+    #[test]
+    fn test_resolve_env_follows_reexports_and_breaks_cycles() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let pkg = temp_dir.path().join("pkg");
+        fs::create_dir_all(&pkg)?;
+
+        // `pkg` re-exports `Thing` from its submodule through the facade.
+        fs::write(pkg.join("__init__.py"), "from .sub import Thing\n")?;
+        fs::write(pkg.join("sub.py"), "class Thing:\n    pass\n")?;
+        // Two modules importing from each other form a resolution cycle.
+        fs::write(pkg.join("a.py"), "from .b import x\n")?;
+        fs::write(pkg.join("b.py"), "from .a import x\n")?;
+
+        let target_files: Vec<(PathBuf, ResolvedFile)> = [
+            "__init__.py",
+            "sub.py",
+            "a.py",
+            "b.py",
+        ]
+        .iter()
+        .map(|name| {
+            let path = pkg.join(name);
+            (path.clone(), ResolvedFile::Root(path))
+        })
+        .collect();
+
+        let mut env = ResolveEnv::build(&target_files);
+
+        // The facade re-export resolves to the defining module, recording the path.
+        let resolved = env.resolve("pkg", "Thing").expect("re-export resolves");
+        assert_eq!(resolved.fully_qualified_name, "pkg.sub.Thing");
+        assert_eq!(resolved.reexport_path, vec!["pkg", "pkg.sub"]);
+
+        // The mutual import must terminate instead of recursing forever.
+        assert!(env.resolve("pkg.a", "x").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_symbol_search_ranks_prefix_matches_first() {
+        let mut candidates = HashMap::new();
+        for name in ["config", "configs", "contig", "reload"] {
+            candidates.insert(
+                name.to_string(),
+                DefinedSymbol {
+                    kind: SymbolKind::Function,
+                    location: PathBuf::from("pkg/config.py"),
+                    docstring: None,
+                    is_public: true,
+                    fully_qualified_name: format!("pkg.config.{name}"),
+                    type_checking_only: false,
+                },
+            );
+        }
+
+        let index = SymbolSearch::from_candidates(&candidates).expect("non-empty candidates");
+        let usage = SymbolUsageMap::new();
+        let matches = index
+            .search("config", 1, &usage, &candidates)
+            .expect("valid query");
+        let names: Vec<&str> = matches.iter().map(|sym| sym.name.as_str()).collect();
+
+        // `reload` is outside one edit and must not match at all.
+        assert!(!names.contains(&"reload"));
+        // Exact match ranks first; among the one-edit hits the prefix match
+        // (`configs`) outranks the substitution (`contig`).
+        assert_eq!(names, ["config", "configs", "contig"]);
+    }
+
+    #[test]
+    fn test_role_classifier_maps_paths_to_roles() {
+        let classifier = RoleClassifier::new(&[], &[], &[]);
+
+        assert_eq!(
+            classifier.classify(Path::new("pkg/consumer.py")),
+            ConsumerRole::Production
+        );
+        assert_eq!(
+            classifier.classify(Path::new("tests/test_api.py")),
+            ConsumerRole::Test
+        );
+        // The `test_` filename convention applies even outside a test directory.
+        assert_eq!(
+            classifier.classify(Path::new("pkg/test_helpers.py")),
+            ConsumerRole::Test
+        );
+        assert_eq!(
+            classifier.classify(Path::new("examples/demo.py")),
+            ConsumerRole::Example
+        );
+        assert_eq!(
+            classifier.classify(Path::new("benches/bench_core.py")),
+            ConsumerRole::Bench
+        );
+
+        // A project can override the default directory names.
+        let custom = RoleClassifier::new(&["spec".to_string()], &[], &[]);
+        assert_eq!(
+            custom.classify(Path::new("spec/thing.py")),
+            ConsumerRole::Test
+        );
+        assert_eq!(
+            custom.classify(Path::new("tests/thing.py")),
+            ConsumerRole::Production
+        );
+    }
+
+    #[test]
+    fn test_usage_tally_aggregates_by_role() {
+        let mut tally = UsageTally::default();
+        tally.record(Path::new("app/main.py"), ConsumerRole::Production);
+        tally.record(Path::new("app/service.py"), ConsumerRole::Production);
+        tally.record(Path::new("tests/test_main.py"), ConsumerRole::Test);
+
+        assert_eq!(tally.count, 3);
+        assert_eq!(tally.importers.len(), 3);
+        assert_eq!(tally.by_role.get(&ConsumerRole::Production), Some(&2));
+        assert_eq!(tally.by_role.get(&ConsumerRole::Test), Some(&1));
+    }
+
+    #[test]
+    fn test_filesystem_resolver_roots_search_paths_and_remappings() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let root = temp_dir.path();
+
+        // First-party source root with a package and a namespace sub-package.
+        fs::create_dir_all(root.join("pkg").join("ns"))?;
+        fs::write(root.join("pkg").join("__init__.py"), "")?;
+        fs::write(root.join("pkg").join("core.py"), "")?;
+
+        // A library search path holding a third-party-but-resolvable package.
+        let libs = root.join("libs");
+        fs::create_dir_all(libs.join("vendor"))?;
+        fs::write(libs.join("vendor").join("__init__.py"), "")?;
+
+        // A remapped logical prefix pointing at a relocated directory.
+        let elsewhere = root.join("elsewhere");
+        fs::create_dir_all(&elsewhere)?;
+        fs::write(elsewhere.join("mod.py"), "")?;
+
+        let resolver = FileSystemResolver::new(
+            root,
+            Vec::new(),
+            vec![libs.clone()],
+            &[format!("company.lib = {}", elsewhere.display())],
+        );
+        let importer = root.join("app.py");
+
+        // A first-party module resolves to its file and is flagged first-party.
+        let hit = resolver
+            .resolve_from(&importer, "pkg.core")
+            .expect("first-party module resolves");
+        assert_eq!(hit.path, root.join("pkg").join("core.py"));
+        assert!(hit.first_party);
+
+        // A PEP 420 namespace package resolves to its directory.
+        let hit = resolver
+            .resolve_from(&importer, "pkg.ns")
+            .expect("namespace package resolves");
+        assert_eq!(hit.path, root.join("pkg").join("ns"));
+
+        // A module on a search path resolves but is not first-party.
+        let hit = resolver
+            .resolve_from(&importer, "vendor")
+            .expect("search-path module resolves");
+        assert!(!hit.first_party);
+
+        // A remapped prefix resolves through its target directory.
+        let hit = resolver
+            .resolve_from(&importer, "company.lib.mod")
+            .expect("remapped module resolves");
+        assert_eq!(hit.path, elsewhere.join("mod.py"));
+        assert!(hit.first_party);
+
+        // An unknown module is third-party and skipped.
+        assert!(resolver.resolve_from(&importer, "requests").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_semantics_augmentation_and_privacy() {
+        let source = concat!(
+            "def public_a():\n    pass\n",
+            "def public_b():\n    pass\n",
+            "def undeclared():\n    pass\n",
+            "def _private():\n    pass\n",
+            "__all__ = ('public_a',)\n",
+            "__all__ += ['public_b']\n",
+            "__all__.append('_private')\n",
+        );
+
+        let symbols: HashMap<String, DefinedSymbol> =
+            extract_file_symbols(Path::new("pkg/mod.py"), source)
+                .into_iter()
+                .collect();
+
+        // Names listed across the tuple, augmentation, and `.append` are public,
+        // even the underscore-prefixed one that `__all__` explicitly exports.
+        assert!(symbols["public_a"].is_public);
+        assert!(symbols["public_b"].is_public);
+        assert!(symbols["_private"].is_public);
+
+        // A top-level symbol absent from `__all__` is forced private even though
+        // its name carries no underscore.
+        assert!(!symbols["undeclared"].is_public);
+    }
+
+    #[test]
+    fn test_annotated_constants_and_type_aliases_are_captured() {
+        let source = concat!(
+            "from typing import Final, TypeAlias\n",
+            "X: int = 5\n",
+            "TIMEOUT: Final = 30\n",
+            "COLOR: typing.Final[str] = \"red\"\n",
+            "Bare: int\n",
+            "LegacyAlias: TypeAlias = int\n",
+            "type Vector = list[float]\n",
+            "_private: Final = 1\n",
+        );
+
+        let symbols: HashMap<String, DefinedSymbol> =
+            extract_file_symbols(Path::new("pkg/mod.py"), source)
+                .into_iter()
+                .collect();
+
+        assert_eq!(symbols["X"].kind, SymbolKind::Variable);
+        assert_eq!(symbols["Bare"].kind, SymbolKind::Variable);
+        assert_eq!(symbols["TIMEOUT"].kind, SymbolKind::Constant);
+        assert_eq!(symbols["COLOR"].kind, SymbolKind::Constant);
+        assert_eq!(symbols["LegacyAlias"].kind, SymbolKind::TypeAlias);
+        assert_eq!(symbols["Vector"].kind, SymbolKind::TypeAlias);
+
+        // The same underscore privacy rule applies to the new kinds.
+        assert!(!symbols["_private"].is_public);
+        assert!(symbols["TIMEOUT"].is_public);
+    }
+
+    #[test]
+    fn test_type_checking_guarded_symbols_are_tagged() {
+        let source = concat!(
+            "import typing as t\n",
+            "from typing import TYPE_CHECKING\n",
+            "def runtime():\n    pass\n",
+            "if TYPE_CHECKING:\n",
+            "    class TypeOnly:\n        pass\n",
+            "    alias = int\n",
+            "if t.TYPE_CHECKING:\n",
+            "    def helper():\n        pass\n",
+        );
+
+        let symbols: HashMap<String, DefinedSymbol> =
+            extract_file_symbols(Path::new("pkg/mod.py"), source)
+                .into_iter()
+                .collect();
+
+        // Runtime definitions are not type-only.
+        assert!(!symbols["runtime"].type_checking_only);
+
+        // Symbols guarded by either the bare flag or the `typing.TYPE_CHECKING`
+        // attribute are still extracted, and tagged as type-checking-only.
+        assert!(symbols["TypeOnly"].type_checking_only);
+        assert!(symbols["alias"].type_checking_only);
+        assert!(symbols["helper"].type_checking_only);
+    }
+
+    #[test]
+    fn test_project_model_src_layout_and_namespace_package() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let project = temp_dir.path().join("proj");
+        let src = project.join("src");
+        // A `src/`-layout package plus a PEP 420 namespace subpackage that has
+        // no `__init__.py` of its own.
+        let ns = src.join("mypkg").join("plugins");
+        fs::create_dir_all(&ns)?;
+        File::create(project.join("pyproject.toml"))?;
+        File::create(src.join("mypkg").join("__init__.py"))?;
+        let core = src.join("mypkg").join("core.py");
+        fs::write(&core, "def f():\n    pass\n")?;
+        let plugin = ns.join("widget.py");
+        fs::write(&plugin, "def g():\n    pass\n")?;
+
+        let model = ProjectModel::discover(&core);
+
+        // Named relative to the `src/` root, not by the `__init__.py` walk.
+        assert_eq!(model.module_name(&core).as_deref(), Some("mypkg.core"));
+        // The namespace package contributes to the dotted path despite having no
+        // `__init__.py`.
+        assert_eq!(
+            model.module_name(&plugin).as_deref(),
+            Some("mypkg.plugins.widget")
+        );
+        // `__init__.py` names the package directory itself.
+        assert_eq!(
+            model.module_name(&src.join("mypkg").join("__init__.py")).as_deref(),
+            Some("mypkg")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_graph_detects_cycle() {
+        let mut graph = ImportGraph::default();
+        let modules: std::collections::BTreeSet<ModuleName> =
+            ["a", "b", "c", "leaf"].iter().map(|s| s.to_string()).collect();
+
+        // a -> b -> c -> a is a cycle; leaf is imported but imports nothing.
+        graph.add_edge("a", "b", &modules);
+        graph.add_edge("b", "c", &modules);
+        graph.add_edge("c", "a", &modules);
+        graph.add_edge("a", "leaf", &modules);
+        graph.edges.entry("leaf".to_string()).or_default();
+
+        // A self-import and a third-party target produce no edge.
+        graph.add_edge("a", "a", &modules);
+        graph.add_edge("a", "numpy", &modules);
+
+        let cycles = graph.circular_imports();
+        assert_eq!(cycles.len(), 1);
+        let cycle = &cycles[0].cycle;
+        // The cycle closes back on its starting module.
+        assert_eq!(cycle.first(), cycle.last());
+        // It contains exactly the three participating modules (plus the repeat).
+        let members: std::collections::BTreeSet<&str> =
+            cycle.iter().map(String::as_str).collect();
+        assert_eq!(
+            members,
+            ["a", "b", "c"].iter().copied().collect::<std::collections::BTreeSet<_>>()
+        );
+    }
+
+    #[test]
+    fn test_reexported_symbol_maps_to_package_facade() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let pkg = temp_dir.path().join("mypkg");
+        fs::create_dir_all(&pkg)?;
+        // `core.py` defines an underscore-private helper that the package
+        // deliberately re-exports from its `__init__.py` façade.
+        fs::write(pkg.join("core.py"), "def _helper():\n    pass\n")?;
+        fs::write(pkg.join("__init__.py"), "from .core import _helper\n")?;
+
+        let core = pkg.join("core.py");
+        let init = pkg.join("__init__.py");
+        let target_files = vec![
+            (core.clone(), ResolvedFile::Root(core.clone())),
+            (init.clone(), ResolvedFile::Root(init.clone())),
+        ];
+
+        let definition = DefinedSymbol {
+            kind: SymbolKind::Function,
+            location: core.clone(),
+            docstring: None,
+            is_public: false,
+            fully_qualified_name: "mypkg.core._helper".to_string(),
+            type_checking_only: false,
+        };
+
+        let mut env = ResolveEnv::build(&target_files);
+        let path = env.reexport_path_for(&definition);
+
+        // The helper is reachable through the package façade, so the re-export
+        // path threads the package `__init__` back to its defining module — the
+        // signal that promotes it to public.
+        assert!(!path.is_empty());
+        assert_eq!(path.first().map(String::as_str), Some("mypkg"));
+        assert_eq!(path.last().map(String::as_str), Some("mypkg.core"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_relative_import_resolves_and_matches_candidate() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let project_dir = temp_dir.path().join("proj");
+
+        // A package `mypkg` with a defining module and a subpackage consumer.
+        let pkg = project_dir.join("mypkg");
+        let sub = pkg.join("sub");
+        fs::create_dir_all(&sub)?;
+        File::create(pkg.join("__init__.py"))?;
+        File::create(sub.join("__init__.py"))?;
+
+        let core_path = pkg.join("core.py");
+        fs::write(&core_path, "def foo():\n    pass\n")?;
+
+        // `mypkg.sub.user` reaches `foo` through a relative `..core` import.
+        let user_path = sub.join("user.py");
+        fs::write(&user_path, "from ..core import foo\n\nfoo()\n")?;
+
+        let mut candidates = HashMap::new();
+        candidates.insert(
+            "foo".to_string(),
+            DefinedSymbol {
+                kind: SymbolKind::Function,
+                location: core_path.clone(),
+                docstring: None,
+                is_public: true,
+                fully_qualified_name: "mypkg.core.foo".to_string(),
+                type_checking_only: false,
+            },
+        );
+
+        // An empty import map forces the direct FQN-reconstruction fallback.
+        // A resolver rooted outside the temp dir resolves nothing, so
+        // `import_names_target` falls back to the bare module-name comparison.
+        let analyzer = ApiAnalyzer::new(
+            candidates,
+            "mypkg".to_string(),
+            ImportMap::default(),
+            FileSystemResolver::new(Path::new("/nonexistent-test-root"), Vec::new(), Vec::new(), &[]),
+            HashSet::new(),
+        );
+
+        let content = fs::read_to_string(&user_path)?;
+        let parsed = ruff_python_parser::parse_module(&content).expect("parses");
+
+        let mut file_state = FileAnalysisState::new();
+        let mut visitor =
+            ApiAnalyzerVisitor::new(&user_path, &analyzer, &mut file_state, ConsumerRole::Test);
+        visitor.process_imports(&parsed.syntax().body);
+        for stmt in &parsed.syntax().body {
+            visitor.visit_stmt(stmt);
+        }
+        let usage = visitor.into_usage();
+
+        // The relative import resolves to `mypkg.core.foo` and is counted.
+        assert_eq!(usage.get("foo").map_or(0, |tally| tally.count), 1);
+
+        Ok(())
+    }
+
     #[test]
     fn test_import_with_different_module_path() -> Result<()> {
         // Create a temporary directory structure for testing
@@ -1616,13 +4777,19 @@ mod tests {
                 docstring: None,
                 is_public: true,
                 fully_qualified_name: "x.y.module.my_symbol".to_string(),
+                type_checking_only: false,
             },
         );
 
-        // Create analyzer with the correct number of parameters
+        // Create analyzer with the correct number of parameters. A resolver
+        // rooted outside the temp dir resolves nothing, so
+        // `import_names_target` falls back to the bare module-name comparison.
         let analyzer = ApiAnalyzer::new(
             candidates,
             "x".to_string(), // Target module name
+            ImportMap::default(),
+            FileSystemResolver::new(Path::new("/nonexistent-test-root"), Vec::new(), Vec::new(), &[]),
+            HashSet::new(),
         );
 
         // Process the import statement
@@ -1634,25 +4801,24 @@ mod tests {
             let mut file_state = FileAnalysisState::new();
 
             // Create a visitor for this file
-            let mut visitor = ApiAnalyzerVisitor::new(&importer_path, &analyzer, &mut file_state);
+            let mut visitor = ApiAnalyzerVisitor::new(
+                &importer_path,
+                &analyzer,
+                &mut file_state,
+                ConsumerRole::Production,
+            );
 
             // Process imports in the file
             visitor.process_imports(&parsed.syntax().body);
 
             // The symbol should NOT be counted since the import path doesn't match
-            let final_usage = match Arc::try_unwrap(analyzer.usage_counts) {
-                Ok(mutex) => mutex.into_inner()?,
-                Err(arc) => arc
-                    .lock()
-                    .map_err(|_| anyhow::anyhow!("Failed to acquire lock"))?
-                    .clone(),
-            };
-
-            let usage = final_usage.get("my_symbol").unwrap();
+            let final_usage = visitor.into_usage();
 
             // Verify that usage count is still 0 since "a.b.my_symbol" != "x.y.module.my_symbol"
-            assert_eq!(usage.0, 0);
-            assert!(usage.1.is_empty());
+            // (an unmatched candidate never gets an entry).
+            let usage = final_usage.get("my_symbol");
+            assert_eq!(usage.map_or(0, |tally| tally.count), 0);
+            assert!(usage.map_or(true, |tally| tally.importers.is_empty()));
         }
 
         Ok(())
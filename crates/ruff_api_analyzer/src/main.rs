@@ -1,10 +1,39 @@
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::time::Instant;
 
 use anyhow::Result;
-use clap::Parser;
-use log::debug;
+use clap::{Args as ClapArgs, Parser, Subcommand, ValueEnum};
+use log::{debug, info};
+
+/// The output format, validated at parse time.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable grouped report (the default).
+    Text,
+    /// A single pretty-printed JSON document.
+    Json,
+    /// One JSON record per line, streamed for large packages.
+    JsonLines,
+    /// SARIF 2.1.0 for GitHub code scanning.
+    Sarif,
+    /// JUnit XML for CI dashboards.
+    Junit,
+}
+
+impl OutputFormat {
+    /// The canonical string the analysis engine matches on.
+    fn as_str(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::JsonLines => "json-lines",
+            OutputFormat::Sarif => "sarif",
+            OutputFormat::Junit => "junit",
+        }
+    }
+}
 
 /// CLI arguments for the standalone API analyzer
 #[derive(Debug, Parser)]
@@ -14,29 +43,61 @@ use log::debug;
     version
 )]
 struct Args {
-    /// The path to the Python module (.py file) or package (directory) to analyze.
-    #[clap()]
-    target: PathBuf,
+    #[command(subcommand)]
+    command: Command,
 
-    /// The output format to use (text/json).
-    #[clap(long = "output-format", short = 'o', default_value = "text")]
-    output_format: String,
+    #[clap(flatten)]
+    global: GlobalArgs,
+}
 
+/// Arguments shared by every subcommand.
+#[derive(Debug, ClapArgs)]
+struct GlobalArgs {
     /// The path to the Python executable to use for venv parsing.
-    #[clap(long = "python")]
+    #[clap(long = "python", global = true)]
     python: Option<PathBuf>,
 
     /// Explicitly specify the project root directory (default: auto-detected from target).
-    #[clap(long = "project-root")]
+    #[clap(long = "project-root", global = true)]
     project_root: Option<PathBuf>,
 
     /// Disable parallel processing for file analysis.
-    #[clap(long)]
+    #[clap(long, global = true)]
     no_parallel: bool,
 
     /// Increase verbosity (can be used multiple times)
-    #[clap(short, long, action = clap::ArgAction::Count)]
+    #[clap(short, long, action = clap::ArgAction::Count, global = true)]
     verbose: u8,
+}
+
+/// The set of subcommands the analyzer exposes.
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Analyze a target and report its effective public API (the default flow).
+    Analyze(AnalyzeArgs),
+
+    /// Compare a target against a baseline (or another target) and report the
+    /// symbol delta.
+    Diff(DiffArgs),
+
+    /// Run the analysis several times on a target and report timings, for
+    /// profiling large packages.
+    Bench(BenchArgs),
+
+    /// Fuzzy-find API symbols by approximate name instead of dumping the whole
+    /// public surface.
+    Search(SearchArgs),
+}
+
+/// Arguments for the `analyze` subcommand.
+#[derive(Debug, ClapArgs)]
+struct AnalyzeArgs {
+    /// The path to the Python module (.py file) or package (directory) to analyze.
+    target: PathBuf,
+
+    /// The output format to use.
+    #[clap(long = "output-format", short = 'o', value_enum, default_value = "text")]
+    output_format: OutputFormat,
 
     /// Output only a sorted summary line for each symbol.
     #[clap(long)]
@@ -46,6 +107,121 @@ struct Args {
     /// and files with names starting with 'test_' or ending with '_test.py' are excluded.
     #[clap(long = "no-ignore-test-files")]
     no_ignore_test_files: bool,
+
+    /// Compare the computed public API against this baseline snapshot and exit
+    /// non-zero if any previously public symbol was removed.
+    #[clap(long = "baseline")]
+    baseline: Option<PathBuf>,
+
+    /// Overwrite the `--baseline` file with the current public API instead of
+    /// comparing against it.
+    #[clap(long = "bless")]
+    bless: bool,
+
+    /// Directory for the incremental analysis cache (default: a
+    /// `.pubscan_cache` directory under the project root).
+    #[clap(long = "cache-dir")]
+    cache_dir: Option<PathBuf>,
+
+    /// Bypass the incremental cache and re-parse every file.
+    #[clap(long = "no-cache")]
+    no_cache: bool,
+
+    /// Ignore any `[tool.pubscan]` configuration discovered in pyproject.toml
+    /// or pubscan.toml.
+    #[clap(long = "isolated")]
+    isolated: bool,
+
+    /// Wrap docstrings in the text report at this column width
+    /// (default: the detected terminal width).
+    #[clap(long = "wrap-width")]
+    wrap_width: Option<usize>,
+
+    /// Extra first-party source root to resolve imports against (repeatable).
+    #[clap(long = "source-root")]
+    source_roots: Vec<PathBuf>,
+
+    /// Library/search path such as a `site-packages` directory, consulted after
+    /// the source roots (repeatable).
+    #[clap(long = "search-path")]
+    search_paths: Vec<PathBuf>,
+
+    /// Import remapping in the `logical.prefix=./actual/path` form (repeatable).
+    #[clap(long = "remap")]
+    remappings: Vec<String>,
+
+    /// Only report symbols reachable from production consumers, hiding API used
+    /// solely by tests, examples, or benchmarks.
+    #[clap(long = "production-only")]
+    production_only: bool,
+
+    /// Flag every cross-module access that reaches a private or undefined name
+    /// on one of the project's own modules, instead of reporting the effective
+    /// public API. Exits non-zero if any violation is found.
+    #[clap(long)]
+    strict: bool,
+
+    /// Also report the package's symbols that are defined but never consumed
+    /// by any of its own sibling files, aggregated across the whole package
+    /// rather than per-file.
+    #[clap(long = "package-report")]
+    package_report: bool,
+
+    /// When set, fuzzy-search the candidate symbols for this query instead of
+    /// reporting the full API (see the `search` subcommand).
+    #[clap(skip)]
+    search_query: Option<String>,
+
+    /// Maximum edit distance (1–2) for `search_query`; 0 selects the default.
+    #[clap(skip)]
+    search_distance: u8,
+}
+
+/// Arguments for the `diff` subcommand.
+#[derive(Debug, ClapArgs)]
+struct DiffArgs {
+    /// The path to the Python module or package to analyze.
+    target: PathBuf,
+
+    /// The old version of the package to compare the target against: a
+    /// directory for a real tree-vs-tree API diff, or a `--bless`-style
+    /// snapshot file to fall back to the usage-snapshot comparison.
+    #[clap(long = "baseline")]
+    baseline: PathBuf,
+
+    /// The output format to use.
+    #[clap(long = "output-format", short = 'o', value_enum, default_value = "text")]
+    output_format: OutputFormat,
+}
+
+/// Arguments for the `search` subcommand.
+#[derive(Debug, ClapArgs)]
+struct SearchArgs {
+    /// The path to the Python module or package to analyze.
+    target: PathBuf,
+
+    /// The approximate symbol name to search for.
+    query: String,
+
+    /// Maximum edit distance to tolerate (1 or 2).
+    #[clap(long, default_value = "2")]
+    distance: u8,
+
+    /// The output format to use. Search results are printed as text
+    /// regardless of this setting.
+    #[clap(long = "output-format", short = 'o', value_enum, default_value = "text")]
+    output_format: OutputFormat,
+}
+
+/// Arguments for the `bench` subcommand.
+#[derive(Debug, ClapArgs)]
+struct BenchArgs {
+    /// The path to the Python module or package to analyze.
+    target: PathBuf,
+
+    /// How many times to run the analysis.
+    #[clap(long, short = 'n', default_value = "5")]
+    iterations: u32,
 }
 
 fn main() -> ExitCode {
@@ -57,7 +233,7 @@ fn main() -> ExitCode {
     let args = Args::parse();
 
     // Set up logging based on verbosity
-    let log_level = match args.verbose {
+    let log_level = match args.global.verbose {
         0 => log::LevelFilter::Info,
         1 => log::LevelFilter::Debug,
         _ => log::LevelFilter::Trace,
@@ -68,86 +244,303 @@ fn main() -> ExitCode {
         .format_timestamp(None)
         .init();
 
-    // Convert relative paths to absolute paths
-    let target_abs = if args.target.is_relative() {
-        match env::current_dir() {
-            Ok(current_dir) => current_dir.join(&args.target),
-            Err(e) => {
-                eprintln!("Error getting current directory: {}", e);
-                return ExitCode::from(1);
-            }
+    match dispatch(args) {
+        Ok(exit_code) => exit_code,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            ExitCode::from(1)
         }
-    } else {
-        args.target.clone()
-    };
+    }
+}
 
-    // Convert project_root to absolute path if it's provided and relative
-    let project_root_abs = args.project_root.map(|path| {
-        if path.is_relative() {
-            match env::current_dir() {
-                Ok(current_dir) => current_dir.join(path),
-                Err(_) => path, // Fall back to the relative path
-            }
-        } else {
-            path
-        }
-    });
-
-    // Convert python executable path to absolute if it's provided and relative
-    let python_abs = args.python.map(|path| {
-        if path.is_relative() {
-            match env::current_dir() {
-                Ok(current_dir) => current_dir.join(path),
-                Err(_) => path, // Fall back to the relative path
-            }
-        } else {
-            path
+/// Resolve a user-supplied path to an absolute path, falling back to the
+/// original path if the current directory cannot be determined.
+fn to_absolute(path: PathBuf) -> PathBuf {
+    if path.is_relative() {
+        match env::current_dir() {
+            Ok(current_dir) => current_dir.join(path),
+            Err(_) => path,
         }
-    });
+    } else {
+        path
+    }
+}
 
-    debug!("Using target path: {}", target_abs.display());
-    if let Some(ref root) = project_root_abs {
-        debug!("Using project root: {}", root.display());
+/// Dispatch the parsed arguments to the appropriate subcommand.
+fn dispatch(args: Args) -> Result<ExitCode> {
+    let global = args.global;
+    match args.command {
+        Command::Analyze(analyze) => run_analyze_command(&global, analyze),
+        Command::Diff(diff) => run_diff_command(&global, diff),
+        Command::Bench(bench) => run_bench_command(&global, bench),
+        Command::Search(search) => run_analyze_command(
+            &global,
+            AnalyzeArgs {
+                target: search.target,
+                output_format: search.output_format,
+                short: false,
+                no_ignore_test_files: false,
+                baseline: None,
+                bless: false,
+                cache_dir: None,
+                no_cache: false,
+                isolated: false,
+                wrap_width: None,
+                source_roots: Vec::new(),
+                search_paths: Vec::new(),
+                remappings: Vec::new(),
+                production_only: false,
+                strict: false,
+                package_report: false,
+                search_query: Some(search.query),
+                search_distance: search.distance,
+            },
+        ),
     }
+}
 
-    // Parse analyzer command using partition
-    let analyze_cmd = ruff::args::AnalyzeApiCommand {
-        target: target_abs,
-        output_format: Some(args.output_format),
-        python: python_abs,
-        project_root: project_root_abs,
+/// Build the shared analyzer command from global + subcommand arguments.
+fn build_command(global: &GlobalArgs, analyze: AnalyzeArgs) -> ruff::args::AnalyzeApiCommand {
+    ruff::args::AnalyzeApiCommand {
+        target: to_absolute(analyze.target),
+        output_format: Some(analyze.output_format.as_str().to_string()),
+        python: global.python.clone().map(to_absolute),
+        project_root: global.project_root.clone().map(to_absolute),
         preview: false,
         no_preview: false,
         detect_string_imports: false,
         target_version: None,
-        no_parallel: args.no_parallel,
-        short: args.short,
-        no_ignore_test_files: args.no_ignore_test_files,
-    };
+        no_parallel: global.no_parallel,
+        short: analyze.short,
+        no_ignore_test_files: analyze.no_ignore_test_files,
+        baseline: analyze.baseline.map(to_absolute),
+        bless: analyze.bless,
+        cache_dir: analyze.cache_dir.map(to_absolute),
+        no_cache: analyze.no_cache,
+        wrap_width: analyze.wrap_width,
+        source_roots: analyze.source_roots.into_iter().map(to_absolute).collect(),
+        search_paths: analyze.search_paths.into_iter().map(to_absolute).collect(),
+        remappings: analyze.remappings,
+        production_only: analyze.production_only,
+        strict: analyze.strict,
+        package_report: analyze.package_report,
+        search_query: analyze.search_query,
+        search_distance: if analyze.search_distance == 0 {
+            2
+        } else {
+            analyze.search_distance
+        },
+    }
+}
+
+/// Run an analysis through the shared `partition`/`run_analyze_api` pipeline.
+fn run_analyze_command(global: &GlobalArgs, analyze: AnalyzeArgs) -> Result<ExitCode> {
+    let isolated = analyze.isolated;
+    let analyze_cmd = build_command(global, analyze);
 
     // Use Default implementation and rely on ExplicitConfigOverrides for more settings
     let mut global_config = ruff::args::GlobalConfigArgs::default();
-    global_config.isolated = true; // Don't try to use .ruff.toml or pyproject.toml
+    global_config.isolated = isolated; // Honor `[tool.pubscan]` unless --isolated
     global_config.config = Vec::new(); // No config options provided
 
-    match analyze_cmd.partition(global_config) {
-        Ok((analyze_args, config_args)) => {
-            // Call into ruff's analyze_api function
-            match run_analyze_api(analyze_args, config_args) {
-                Ok(exit_status) => exit_status.into(),
-                Err(err) => {
-                    eprintln!("Error: {}", err);
-                    ExitCode::from(1)
-                }
-            }
+    let (analyze_args, config_args) = analyze_cmd.partition(global_config)?;
+    debug!(
+        "Running API analysis on target: {}",
+        analyze_args.target_path.display()
+    );
+    Ok(run_analyze_api(analyze_args, config_args)?.into())
+}
+
+/// Recursively collect every `.py` file under `dir`.
+fn collect_python_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_python_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "py") {
+            files.push(path);
         }
-        Err(err) => {
-            eprintln!("Error: {}", err);
-            ExitCode::from(1)
+    }
+}
+
+/// Run a tree-vs-tree public-API diff: `diff.baseline` is the old version of
+/// the package and `diff.target` is the new one. Exits non-zero when the diff
+/// contains a breaking change, the same way `analyze --baseline` gates on a
+/// removed symbol.
+///
+/// When `baseline` isn't a directory, it's a `--bless`-style snapshot file
+/// instead, so the comparison falls back to the usage-snapshot pipeline that
+/// already backs `analyze --baseline`.
+fn run_diff_command(global: &GlobalArgs, diff: DiffArgs) -> Result<ExitCode> {
+    let old_root = to_absolute(diff.baseline);
+    let new_root = to_absolute(diff.target);
+
+    if !old_root.is_dir() {
+        return run_analyze_command(
+            global,
+            AnalyzeArgs {
+                target: new_root,
+                output_format: diff.output_format,
+                short: false,
+                no_ignore_test_files: false,
+                baseline: Some(old_root),
+                bless: false,
+                cache_dir: None,
+                no_cache: false,
+                isolated: false,
+                wrap_width: None,
+                source_roots: Vec::new(),
+                search_paths: Vec::new(),
+                remappings: Vec::new(),
+                production_only: false,
+                strict: false,
+                package_report: false,
+                search_query: None,
+                search_distance: 0,
+            },
+        );
+    }
+
+    let mut old_files = Vec::new();
+    let mut new_files = Vec::new();
+    collect_python_files(&old_root, &mut old_files);
+    collect_python_files(&new_root, &mut new_files);
+    old_files.sort();
+    new_files.sort();
+
+    let api_diff = ruff_linter::diff::diff_public_api(
+        ruff_linter::package::PackageRoot::root(&old_root),
+        &old_files,
+        ruff_linter::package::PackageRoot::root(&new_root),
+        &new_files,
+    );
+
+    match diff.output_format {
+        OutputFormat::Json | OutputFormat::JsonLines => {
+            println!("{}", serde_json::to_string_pretty(&api_diff.changes)?);
+        }
+        _ => print_api_diff(&api_diff),
+    }
+
+    Ok(if api_diff.has_breaking() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Print a tree-vs-tree public-API diff as text.
+fn print_api_diff(diff: &ruff_linter::diff::ApiDiff) {
+    if diff.changes.is_empty() {
+        println!("No public-API changes.");
+        return;
+    }
+    for change in &diff.changes {
+        match &change.detail {
+            Some(detail) => println!(
+                "{:?} {:?} {} ({})",
+                change.severity, change.kind, change.qualified_name, detail
+            ),
+            None => println!(
+                "{:?} {:?} {}",
+                change.severity, change.kind, change.qualified_name
+            ),
         }
     }
 }
 
+/// Run the analysis `iterations` times and report per-run and per-phase timings.
+fn run_bench_command(global: &GlobalArgs, bench: BenchArgs) -> Result<ExitCode> {
+    let target = to_absolute(bench.target);
+    let mut durations = Vec::with_capacity(bench.iterations as usize);
+    let mut phase_timings = Vec::with_capacity(bench.iterations as usize);
+
+    for i in 0..bench.iterations {
+        let analyze_cmd = build_command(
+            global,
+            AnalyzeArgs {
+                target: target.clone(),
+                output_format: OutputFormat::Text,
+                short: true,
+                no_ignore_test_files: false,
+                baseline: None,
+                bless: false,
+                cache_dir: None,
+                no_cache: false,
+                isolated: true,
+                wrap_width: None,
+                source_roots: Vec::new(),
+                search_paths: Vec::new(),
+                remappings: Vec::new(),
+                production_only: false,
+                strict: false,
+                package_report: false,
+                search_query: None,
+                search_distance: 0,
+            },
+        );
+
+        let mut global_config = ruff::args::GlobalConfigArgs::default();
+        global_config.isolated = true;
+        global_config.config = Vec::new();
+
+        let (analyze_args, config_args) = analyze_cmd.partition(global_config)?;
+
+        let mut timings = ruff::commands::analyze_api::PhaseTimings::default();
+        let start = Instant::now();
+        ruff::commands::analyze_api::analyze_api_with_timings(
+            &analyze_args,
+            &config_args,
+            Some(&mut timings),
+        )?;
+        let elapsed = start.elapsed();
+        durations.push(elapsed);
+        info!("iteration {}: {:.3?}", i + 1, elapsed);
+        phase_timings.push(timings);
+    }
+
+    report_bench(&target, &durations, &phase_timings);
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Print summary statistics for a benchmark run, including the mean time
+/// spent in each analysis phase so a slow run on a large package can be
+/// attributed to discovery, parsing, resolution, or usage analysis.
+fn report_bench(
+    target: &Path,
+    durations: &[std::time::Duration],
+    phase_timings: &[ruff::commands::analyze_api::PhaseTimings],
+) {
+    if durations.is_empty() {
+        return;
+    }
+
+    let total: std::time::Duration = durations.iter().sum();
+    let mean = total / durations.len() as u32;
+    let min = durations.iter().min().copied().unwrap_or_default();
+    let max = durations.iter().max().copied().unwrap_or_default();
+
+    println!("Benchmark for {} ({} runs):", target.display(), durations.len());
+    println!("  mean {:.3?}", mean);
+    println!("  min  {:.3?}", min);
+    println!("  max  {:.3?}", max);
+
+    let runs = phase_timings.len() as u32;
+    let discover: std::time::Duration = phase_timings.iter().map(|t| t.discover).sum::<std::time::Duration>() / runs;
+    let parse: std::time::Duration = phase_timings.iter().map(|t| t.parse).sum::<std::time::Duration>() / runs;
+    let resolve: std::time::Duration = phase_timings.iter().map(|t| t.resolve).sum::<std::time::Duration>() / runs;
+    let analyze: std::time::Duration = phase_timings.iter().map(|t| t.analyze).sum::<std::time::Duration>() / runs;
+
+    println!("  phases (mean):");
+    println!("    discover {:.3?}", discover);
+    println!("    parse    {:.3?}", parse);
+    println!("    resolve  {:.3?}", resolve);
+    println!("    analyze  {:.3?}", analyze);
+}
+
 /// Wrapper function to call ruff's analyze_api functionality
 fn run_analyze_api(
     args: ruff::args::AnalyzeApiArgs,
@@ -0,0 +1,289 @@
+//! End-to-end tests for the `api-analyzer` binary's subcommand glue
+//! (`dispatch`/`build_command`/`run_*_command`): the arg-plumbing and
+//! exit-code mapping that unit tests against `ruff_linter`'s internal
+//! functions never exercise.
+
+#![cfg(not(target_arch = "wasm32"))]
+#![cfg(not(windows))]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use anyhow::Result;
+use insta_cmd::get_cargo_bin;
+use tempfile::TempDir;
+
+fn command() -> Command {
+    Command::new(get_cargo_bin("api-analyzer"))
+}
+
+fn run(command: &mut Command) -> Result<Output> {
+    Ok(command.output()?)
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// A minimal package with one public function, one private helper, and one
+/// external consumer that calls the public function. A `pyproject.toml` marks
+/// `root` as the project root, so the consumer beside the package is scanned.
+fn setup_package(root: &Path) -> Result<()> {
+    fs::write(root.join("pyproject.toml"), "")?;
+    fs::create_dir_all(root.join("mypackage"))?;
+    fs::write(root.join("mypackage").join("__init__.py"), "")?;
+    fs::write(
+        root.join("mypackage").join("core.py"),
+        "def add(a, b):\n    return a + b\n\n\ndef _secret():\n    pass\n",
+    )?;
+    fs::write(
+        root.join("client.py"),
+        "from mypackage.core import add\n\nadd(1, 2)\n",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn analyze_reports_public_api_and_exits_success() -> Result<()> {
+    let tempdir = TempDir::new()?;
+    let root = tempdir.path();
+    setup_package(root)?;
+
+    let output = run(command()
+        .arg("analyze")
+        .arg(root.join("mypackage"))
+        .current_dir(root))?;
+
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout(&output).contains("add"));
+
+    Ok(())
+}
+
+#[test]
+fn analyze_json_output_format_is_plumbed_through() -> Result<()> {
+    let tempdir = TempDir::new()?;
+    let root = tempdir.path();
+    setup_package(root)?;
+
+    let output = run(command()
+        .arg("analyze")
+        .arg(root.join("mypackage"))
+        .arg("--output-format=json")
+        .current_dir(root))?;
+
+    assert!(output.status.success());
+    // A `--short` text report would never contain this; confirms the flag
+    // actually reached `build_command` -> `AnalyzeApiArgs.output_format`.
+    assert!(serde_json::from_str::<serde_json::Value>(&stdout(&output)).is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn analyze_strict_exits_nonzero_on_boundary_violation() -> Result<()> {
+    let tempdir = TempDir::new()?;
+    let root = tempdir.path();
+    setup_package(root)?;
+    // A private access from outside the defining module.
+    fs::write(
+        root.join("client.py"),
+        "from mypackage.core import _secret\n\n_secret()\n",
+    )?;
+
+    let output = run(command()
+        .arg("analyze")
+        .arg(root.join("mypackage"))
+        .arg("--strict")
+        .current_dir(root))?;
+
+    assert!(!output.status.success());
+    assert!(stdout(&output).contains("private"));
+
+    Ok(())
+}
+
+#[test]
+fn analyze_strict_exits_success_with_no_violations() -> Result<()> {
+    let tempdir = TempDir::new()?;
+    let root = tempdir.path();
+    setup_package(root)?;
+
+    let output = run(command()
+        .arg("analyze")
+        .arg(root.join("mypackage"))
+        .arg("--strict")
+        .current_dir(root))?;
+
+    assert!(output.status.success());
+
+    Ok(())
+}
+
+#[test]
+fn analyze_bless_then_baseline_gates_on_removed_symbol() -> Result<()> {
+    let tempdir = TempDir::new()?;
+    let root = tempdir.path();
+    setup_package(root)?;
+    let baseline = root.join("baseline.json");
+
+    // Bless the current surface.
+    let bless = run(command()
+        .arg("analyze")
+        .arg(root.join("mypackage"))
+        .arg("--bless")
+        .arg("--baseline")
+        .arg(&baseline)
+        .current_dir(root))?;
+    assert!(bless.status.success(), "stderr: {}", String::from_utf8_lossy(&bless.stderr));
+    assert!(baseline.is_file());
+
+    // Unchanged surface: still gates clean.
+    let unchanged = run(command()
+        .arg("analyze")
+        .arg(root.join("mypackage"))
+        .arg("--baseline")
+        .arg(&baseline)
+        .current_dir(root))?;
+    assert!(unchanged.status.success());
+
+    // Remove the public symbol the baseline captured; `--baseline` must now
+    // gate CI by exiting non-zero rather than silently passing.
+    fs::write(root.join("mypackage").join("core.py"), "def _secret():\n    pass\n")?;
+    fs::write(root.join("client.py"), "")?;
+
+    let after_removal = run(command()
+        .arg("analyze")
+        .arg(root.join("mypackage"))
+        .arg("--baseline")
+        .arg(&baseline)
+        .current_dir(root))?;
+    assert!(!after_removal.status.success());
+
+    Ok(())
+}
+
+#[test]
+fn diff_tree_vs_tree_flags_breaking_change_and_exits_nonzero() -> Result<()> {
+    let old_dir = TempDir::new()?;
+    let new_dir = TempDir::new()?;
+
+    fs::create_dir_all(old_dir.path().join("mypackage"))?;
+    fs::write(
+        old_dir.path().join("mypackage").join("core.py"),
+        "def add(a, b):\n    return a + b\n",
+    )?;
+
+    fs::create_dir_all(new_dir.path().join("mypackage"))?;
+    fs::write(
+        new_dir.path().join("mypackage").join("core.py"),
+        "def multiply(a, b):\n    return a * b\n",
+    )?;
+
+    let output = run(command()
+        .arg("diff")
+        .arg(new_dir.path().join("mypackage"))
+        .arg("--baseline")
+        .arg(old_dir.path().join("mypackage")))?;
+
+    assert!(!output.status.success());
+    assert!(stdout(&output).contains("add"));
+
+    Ok(())
+}
+
+#[test]
+fn diff_tree_vs_tree_exits_success_with_no_breaking_changes() -> Result<()> {
+    let old_dir = TempDir::new()?;
+    let new_dir = TempDir::new()?;
+
+    for dir in [&old_dir, &new_dir] {
+        fs::create_dir_all(dir.path().join("mypackage"))?;
+        fs::write(
+            dir.path().join("mypackage").join("core.py"),
+            "def add(a, b):\n    return a + b\n",
+        )?;
+    }
+
+    let output = run(command()
+        .arg("diff")
+        .arg(new_dir.path().join("mypackage"))
+        .arg("--baseline")
+        .arg(old_dir.path().join("mypackage")))?;
+
+    assert!(output.status.success());
+
+    Ok(())
+}
+
+#[test]
+fn search_finds_fuzzy_match_and_exits_success() -> Result<()> {
+    let tempdir = TempDir::new()?;
+    let root = tempdir.path();
+    setup_package(root)?;
+
+    let output = run(command()
+        .arg("search")
+        .arg(root.join("mypackage"))
+        .arg("ad") // fuzzy match against `add`
+        .current_dir(root))?;
+
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("add"));
+
+    Ok(())
+}
+
+#[test]
+fn bench_reports_per_run_and_per_phase_timings() -> Result<()> {
+    let tempdir = TempDir::new()?;
+    let root = tempdir.path();
+    setup_package(root)?;
+
+    let output = run(command()
+        .arg("bench")
+        .arg(root.join("mypackage"))
+        .arg("-n")
+        .arg("2")
+        .current_dir(root))?;
+
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let text = stdout(&output);
+    assert!(text.contains("mean"));
+    assert!(text.contains("phases (mean):"));
+    assert!(text.contains("discover"));
+    assert!(text.contains("parse"));
+    assert!(text.contains("resolve"));
+    assert!(text.contains("analyze"));
+
+    Ok(())
+}
+
+#[test]
+fn analyze_package_report_flags_symbol_unused_within_package() -> Result<()> {
+    let tempdir = TempDir::new()?;
+    let root = tempdir.path();
+    fs::create_dir_all(root.join("mypackage"))?;
+    fs::write(root.join("mypackage").join("__init__.py"), "")?;
+    // `dead` is never called by any sibling file in the package.
+    fs::write(
+        root.join("mypackage").join("core.py"),
+        "def add(a, b):\n    return a + b\n\n\ndef dead():\n    pass\n",
+    )?;
+    fs::write(
+        root.join("mypackage").join("app.py"),
+        "from mypackage.core import add\n\nadd(1, 2)\n",
+    )?;
+
+    let output = run(command()
+        .arg("analyze")
+        .arg(root.join("mypackage"))
+        .arg("--package-report")
+        .current_dir(root))?;
+
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("dead"));
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! Diff the public API of two versions of a package — an old tree against a new
+//! one — and report every breaking or additive change, exiting non-zero when a
+//! breaking change is present so the diff can gate CI.
+//!
+//! Usage: `cargo run --example api_diff -- path/to/old path/to/new`
+
+use std::path::{Path, PathBuf};
+
+use ruff_linter::diff::diff_public_api;
+use ruff_linter::package::PackageRoot;
+
+/// Recursively collect every `.py` file under `dir`.
+fn collect_python_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_python_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "py") {
+            files.push(path);
+        }
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let (Some(old), Some(new)) = (args.next(), args.next()) else {
+        eprintln!("usage: api_diff <old-dir> <new-dir>");
+        std::process::exit(2);
+    };
+
+    let old_root = PathBuf::from(old);
+    let new_root = PathBuf::from(new);
+    let mut old_files = Vec::new();
+    let mut new_files = Vec::new();
+    collect_python_files(&old_root, &mut old_files);
+    collect_python_files(&new_root, &mut new_files);
+    old_files.sort();
+    new_files.sort();
+
+    let diff = diff_public_api(
+        PackageRoot::root(&old_root),
+        &old_files,
+        PackageRoot::root(&new_root),
+        &new_files,
+    );
+
+    if diff.changes.is_empty() {
+        println!("No public-API changes.");
+        return;
+    }
+
+    for change in &diff.changes {
+        match &change.detail {
+            Some(detail) => println!(
+                "{:?} {:?} {} ({})",
+                change.severity, change.kind, change.qualified_name, detail
+            ),
+            None => println!(
+                "{:?} {:?} {}",
+                change.severity, change.kind, change.qualified_name
+            ),
+        }
+    }
+
+    if diff.has_breaking() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,58 @@
+//! Run strict boundary analysis over a package directory, reporting every
+//! access that reaches a private or undefined name on one of the package's own
+//! modules.
+//!
+//! Usage: `cargo run --example strict_analysis -- path/to/package`
+
+use std::path::{Path, PathBuf};
+
+use ruff_linter::package::PackageRoot;
+use ruff_linter::strict::{analyze_package_strict, DiagnosticKind};
+
+/// Recursively collect every `.py` file under `dir`.
+fn collect_python_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_python_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "py") {
+            files.push(path);
+        }
+    }
+}
+
+fn main() {
+    let Some(target) = std::env::args().nth(1) else {
+        eprintln!("usage: strict_analysis <package-dir>");
+        std::process::exit(2);
+    };
+
+    let root = PathBuf::from(target);
+    let mut files = Vec::new();
+    collect_python_files(&root, &mut files);
+    files.sort();
+
+    let diagnostics = analyze_package_strict(PackageRoot::root(&root), &files);
+    if diagnostics.is_empty() {
+        println!("No boundary violations found.");
+        return;
+    }
+
+    for diagnostic in &diagnostics {
+        let kind = match diagnostic.kind {
+            DiagnosticKind::Private => "private",
+            DiagnosticKind::Undefined => "undefined",
+        };
+        println!(
+            "{}:{}: {} access to {}",
+            diagnostic.file.display(),
+            diagnostic.line,
+            kind,
+            diagnostic.accessed
+        );
+    }
+    std::process::exit(1);
+}
@@ -0,0 +1,393 @@
+//! Package-level public-API analysis.
+//!
+//! [`analyze_source_code`](crate::analyze_source_code) describes a single file;
+//! [`analyze_package_api`] stitches those per-file views together so the tool
+//! can answer a package-wide question: which of `mypackage`'s public symbols are
+//! actually consumed by its other files, and which are defined but never used.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use ruff_python_ast as ast;
+use ruff_python_ast::ExprContext;
+
+use crate::package::PackageRoot;
+use crate::{analyze_source_code, TopLevelBindingType};
+
+/// Aggregate usage of a single public symbol across a whole package.
+#[derive(Debug, Clone)]
+pub struct SymbolUsage {
+    /// The symbol's bare name (`add`).
+    pub name: String,
+    /// The symbol's fully qualified name (`mypackage.core.add`).
+    pub qualified_name: String,
+    /// The file that defines the symbol.
+    pub defined_in: PathBuf,
+    /// Total number of references to the symbol across every referencing file.
+    pub usage_count: usize,
+    /// The set of files that reference the symbol, in path order.
+    pub referencing_files: BTreeSet<PathBuf>,
+}
+
+impl SymbolUsage {
+    /// Whether the symbol is defined but never consumed anywhere in the package.
+    pub fn is_unused(&self) -> bool {
+        self.usage_count == 0
+    }
+}
+
+/// The result of [`analyze_package_api`]: every locally-defined symbol in the
+/// package, keyed by fully qualified name, with its aggregate usage.
+#[derive(Debug, Clone, Default)]
+pub struct ApiReport {
+    symbols: BTreeMap<String, SymbolUsage>,
+}
+
+impl ApiReport {
+    /// Every symbol in the report, ordered by fully qualified name.
+    pub fn symbols(&self) -> impl Iterator<Item = &SymbolUsage> {
+        self.symbols.values()
+    }
+
+    /// The symbols that are defined in the package but referenced by none of its
+    /// files — the "defined but never consumed" public surface.
+    pub fn unused(&self) -> impl Iterator<Item = &SymbolUsage> {
+        self.symbols.values().filter(|sym| sym.is_unused())
+    }
+}
+
+/// Analyze every file in `files` as part of `package`, resolving each
+/// `module.symbol` access and `from pkg.module import symbol` against the
+/// symbols the package defines, and return per-symbol aggregate usage counts
+/// plus the set of referencing files.
+pub fn analyze_package_api(package: PackageRoot, files: &[PathBuf]) -> ApiReport {
+    analyze_package_api_with_parallelism(package, files, true)
+}
+
+/// Same as [`analyze_package_api`], but lets the caller disable the rayon
+/// thread pool (e.g. the CLI's `--no-parallel` flag) and fall back to running
+/// both phases sequentially, for deterministic ordering during debugging.
+pub fn analyze_package_api_with_parallelism(
+    package: PackageRoot,
+    files: &[PathBuf],
+    parallel: bool,
+) -> ApiReport {
+    // Phase 1 — definitions. Extracting a file's definitions, re-export edges,
+    // and its imports from sibling modules depends on nothing but the file
+    // itself, so every file is parsed in parallel. Resolving the whole symbol
+    // table up front also sidesteps import cycles: only the usage-attribution
+    // step below is order-sensitive, and it needs a complete table regardless.
+    let facts: Vec<FileFacts> = if parallel {
+        files
+            .par_iter()
+            .filter_map(|file| FileFacts::extract(file, &package))
+            .collect()
+    } else {
+        files
+            .iter()
+            .filter_map(|file| FileFacts::extract(file, &package))
+            .collect()
+    };
+
+    let mut symbols: BTreeMap<String, SymbolUsage> = BTreeMap::new();
+    let mut reexports: HashMap<String, String> = HashMap::new();
+    for fact in &facts {
+        for (name, qualified) in &fact.defined {
+            symbols.entry(qualified.clone()).or_insert_with(|| SymbolUsage {
+                name: name.clone(),
+                qualified_name: qualified.clone(),
+                defined_in: fact.path.clone(),
+                usage_count: 0,
+                referencing_files: BTreeSet::new(),
+            });
+        }
+        for (alias, target) in &fact.reexports {
+            reexports.insert(alias.clone(), target.clone());
+        }
+    }
+
+    // Phase 2 — usage attribution, in dependency order. Build a DAG whose edge
+    // `utils -> core` means `utils` consumes `core`'s symbols; a node becomes
+    // ready once every module it depends on has been symbol-resolved. Since
+    // phase 1 resolved every definition, each wave's files can attribute usage
+    // in parallel against the tables of the waves before them, and any import
+    // cycle simply lands in the final wave.
+    let modules: HashMap<&str, usize> = facts
+        .iter()
+        .enumerate()
+        .map(|(index, fact)| (fact.module.as_str(), index))
+        .collect();
+
+    for wave in dependency_waves(&facts, &modules) {
+        let attribute_one = |&index: &usize| {
+            let fact = &facts[index];
+            references_in(&fact.source, &fact.aliases)
+                .into_iter()
+                .map(|reference| (resolve_reexport(&reexports, reference), fact.path.clone()))
+                .collect::<Vec<_>>()
+        };
+        let attributed: Vec<(String, PathBuf)> = if parallel {
+            wave.par_iter().flat_map(attribute_one).collect()
+        } else {
+            wave.iter().flat_map(attribute_one).collect()
+        };
+
+        for (qualified, file) in attributed {
+            if let Some(usage) = symbols.get_mut(&qualified) {
+                usage.usage_count += 1;
+                usage.referencing_files.insert(file);
+            }
+        }
+    }
+
+    ApiReport { symbols }
+}
+
+/// The per-file facts extracted in phase 1: enough to resolve the package's
+/// symbol table and order the usage-attribution phase without reparsing.
+struct FileFacts {
+    path: PathBuf,
+    source: String,
+    module: String,
+    /// `(name, fully_qualified_name)` for each locally-defined symbol.
+    defined: Vec<(String, String)>,
+    /// Re-export edges `(this_module.alias, target_qualified_name)`.
+    reexports: Vec<(String, String)>,
+    /// Imported alias -> target dotted name, for resolving bare references.
+    aliases: HashMap<String, String>,
+    /// The sibling modules this file imports from (its dependency edges).
+    deps: HashSet<String>,
+}
+
+impl FileFacts {
+    /// Extract a file's facts, or `None` if it cannot be read.
+    fn extract(file: &Path, package: &PackageRoot) -> Option<Self> {
+        let source = std::fs::read_to_string(file).ok()?;
+        let module = package.module_name(file);
+
+        let mut defined = Vec::new();
+        let mut reexports = Vec::new();
+        let mut aliases = HashMap::new();
+        let mut deps = HashSet::new();
+        for binding in analyze_source_code(file, package.clone(), &source) {
+            match binding.binding_type {
+                TopLevelBindingType::LocallyDefined => {
+                    if let Some(qualified) = binding.qualified_name {
+                        defined.push((binding.name, qualified));
+                    }
+                }
+                TopLevelBindingType::Imported => {
+                    if let Some(target) = binding.qualified_name {
+                        reexports.push((format!("{module}.{}", binding.name), target.clone()));
+                        aliases.insert(binding.name, target.clone());
+                        // Both the imported module and its parent are candidate
+                        // dependency edges; the graph keeps only those that name
+                        // a sibling module.
+                        deps.insert(target.clone());
+                        if let Some((parent, _)) = target.rsplit_once('.') {
+                            deps.insert(parent.to_string());
+                        }
+                    }
+                }
+            }
+        }
+
+        Some(FileFacts {
+            path: file.to_path_buf(),
+            source,
+            module,
+            defined,
+            reexports,
+            aliases,
+            deps,
+        })
+    }
+}
+
+/// Group files into dependency "waves" via Kahn's algorithm over the
+/// intra-package import graph: a file is ready once every sibling module it
+/// imports from sits in an earlier wave. Files caught in an import cycle are
+/// emitted together in a final wave — their definitions are already resolved,
+/// so only their usage attribution was deferred.
+fn dependency_waves(facts: &[FileFacts], modules: &HashMap<&str, usize>) -> Vec<Vec<usize>> {
+    // Count each file's unresolved sibling dependencies.
+    let mut pending: Vec<HashSet<usize>> = facts
+        .iter()
+        .map(|fact| {
+            fact.deps
+                .iter()
+                .filter(|dep| dep.as_str() != fact.module)
+                .filter_map(|dep| modules.get(dep.as_str()).copied())
+                .collect()
+        })
+        .collect();
+
+    let mut resolved = vec![false; facts.len()];
+    let mut waves = Vec::new();
+    loop {
+        let ready: Vec<usize> = (0..facts.len())
+            .filter(|&index| !resolved[index] && pending[index].is_empty())
+            .collect();
+        if ready.is_empty() {
+            break;
+        }
+        for &index in &ready {
+            resolved[index] = true;
+        }
+        for set in &mut pending {
+            for index in &ready {
+                set.remove(index);
+            }
+        }
+        waves.push(ready);
+    }
+
+    // Anything still unresolved is part of a cycle; run it last.
+    let cycle: Vec<usize> = (0..facts.len()).filter(|&index| !resolved[index]).collect();
+    if !cycle.is_empty() {
+        waves.push(cycle);
+    }
+    waves
+}
+
+/// Follow a chain of re-export edges (`a -> b -> c`) until reaching a name with
+/// no further edge — the original definition, or an external module. Cycles are
+/// collapsed safely: a name already seen terminates the walk.
+fn resolve_reexport(reexports: &HashMap<String, String>, name: String) -> String {
+    let mut seen = BTreeSet::new();
+    let mut current = name;
+    while let Some(next) = reexports.get(&current) {
+        if !seen.insert(current.clone()) {
+            break;
+        }
+        current = next.clone();
+    }
+    current
+}
+
+/// Collect every dotted name referenced in `source`, resolving imported aliases
+/// back to their target so `from pkg import add; add()` attributes to
+/// `pkg.add`. Duplicates are intentional — they drive the usage count.
+fn references_in(source: &str, aliases: &HashMap<String, String>) -> Vec<String> {
+    let Ok(parsed) = ruff_python_parser::parse_module(source) else {
+        return Vec::new();
+    };
+
+    let mut collector = ReferenceCollector {
+        aliases,
+        references: Vec::new(),
+    };
+    for stmt in &parsed.syntax().body {
+        collector.visit_stmt(stmt);
+    }
+    collector.references
+}
+
+/// Walks a module body collecting the dotted names it references.
+///
+/// Only actual load sites count: an `import` / `from ... import ...` statement
+/// binds a name rather than consuming one, so re-export façades never credit
+/// their targets with a usage they didn't earn.
+struct ReferenceCollector<'a> {
+    aliases: &'a HashMap<String, String>,
+    references: Vec<String>,
+}
+
+impl ReferenceCollector<'_> {
+    fn visit_stmt(&mut self, stmt: &ast::Stmt) {
+        ast::visitor::source_order::walk_stmt(self, stmt);
+    }
+}
+
+impl ast::visitor::source_order::SourceOrderVisitor<'_> for ReferenceCollector<'_> {
+    fn visit_expr(&mut self, expr: &ast::Expr) {
+        match expr {
+            // `pkg.module.symbol` — the dotted path is the reference.
+            ast::Expr::Attribute(_) => {
+                if let Some(path) = attribute_path(expr) {
+                    self.references.push(path);
+                }
+            }
+            // A bare `symbol` load resolves through the alias table.
+            ast::Expr::Name(name) if name.ctx == ExprContext::Load => {
+                if let Some(target) = self.aliases.get(name.id.as_str()) {
+                    self.references.push(target.clone());
+                }
+            }
+            _ => {}
+        }
+        ast::visitor::source_order::walk_expr(self, expr);
+    }
+}
+
+/// The dotted name of an attribute chain that bottoms out at a bare name, e.g.
+/// `a.b.c` -> `Some("a.b.c")`. Returns `None` for chains rooted in a call or
+/// subscript (`f().x`, `d['k'].y`).
+fn attribute_path(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::Name(name) => Some(name.id.to_string()),
+        ast::Expr::Attribute(attr) => {
+            let base = attribute_path(&attr.value)?;
+            Some(format!("{base}.{}", attr.attr))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::test_support::package;
+
+    use super::*;
+
+    #[test]
+    fn analyze_package_api_counts_real_calls_not_reexports() {
+        // `add` is re-exported by the facade and actually called by `app`;
+        // `helper` is defined but only ever re-exported, never called.
+        let (_temp, package, files) = package(&[
+            ("__init__.py", "from .core import add, helper\n"),
+            (
+                "core.py",
+                "def add(x, y):\n    return x + y\n\n\ndef helper():\n    pass\n",
+            ),
+            ("app.py", "from .core import add\n\n\ndef run():\n    return add(1, 2)\n"),
+        ]);
+
+        let report = analyze_package_api(package, &files);
+        let usage: HashMap<_, _> = report
+            .symbols()
+            .map(|sym| (sym.qualified_name.as_str(), sym.usage_count))
+            .collect();
+
+        // The call site in `app` credits `add`; the facade re-export alone does not.
+        assert_eq!(usage.get("pkg.core.add"), Some(&1));
+
+        // `helper` is re-exported but never called, so it stays unused; `run`
+        // has no caller either.
+        let unused: Vec<_> = report.unused().map(|sym| sym.qualified_name.as_str()).collect();
+        assert_eq!(unused, ["pkg.app.run", "pkg.core.helper"]);
+    }
+
+    #[test]
+    fn analyze_package_api_terminates_on_import_cycle() {
+        // `a` and `b` import from each other; usage attribution must still
+        // terminate (the cycle lands in the final wave) and resolve symbols.
+        let (_temp, package, files) = package(&[
+            ("a.py", "from .b import beta\n\n\ndef alpha():\n    return beta()\n"),
+            ("b.py", "from .a import alpha\n\n\ndef beta():\n    return 1\n"),
+        ]);
+
+        let report = analyze_package_api(package, &files);
+        let usage: HashMap<_, _> = report
+            .symbols()
+            .map(|sym| (sym.qualified_name.as_str(), sym.usage_count))
+            .collect();
+
+        assert_eq!(usage.get("pkg.b.beta"), Some(&1));
+        // `alpha` is imported by `b` but never called, so it remains unused.
+        assert_eq!(usage.get("pkg.a.alpha"), Some(&0));
+    }
+}
@@ -0,0 +1,371 @@
+//! Public-surface diffing between two versions of a package.
+//!
+//! Where [`api`](crate::api) asks how a single tree's public surface is used,
+//! this module compares two trees — an old release and a new one — and reports
+//! what changed: symbols that disappeared, symbols whose kind flipped
+//! (function -> class), `__all__` entries that were dropped, and functions
+//! whose signature shifted. Each change is classified as `breaking` or
+//! `additive` so the diff can gate CI the way a compile check gates a build.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use ruff_python_ast as ast;
+use serde::Serialize;
+
+use crate::package::PackageRoot;
+
+/// The kind of a public symbol, as it appears on a module's surface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolKind {
+    Function,
+    Class,
+    Variable,
+    TypeAlias,
+}
+
+impl std::fmt::Display for SymbolKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SymbolKind::Function => "function",
+            SymbolKind::Class => "class",
+            SymbolKind::Variable => "variable",
+            SymbolKind::TypeAlias => "type alias",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How disruptive a change is for downstream consumers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// A change that can break existing callers (a removal, kind flip,
+    /// signature change, or dropped `__all__` entry).
+    Breaking,
+    /// A backwards-compatible addition.
+    Additive,
+}
+
+/// The category of a single public-surface change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeKind {
+    Removed,
+    Added,
+    KindChanged,
+    SignatureChanged,
+    AllEntryDropped,
+}
+
+/// A single difference between the two surfaces.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiChange {
+    pub kind: ChangeKind,
+    pub severity: Severity,
+    /// The fully qualified name the change concerns.
+    pub qualified_name: String,
+    /// A human-readable description of the change, when one adds detail beyond
+    /// the kind (e.g. `function -> class`, `(a, b) -> (a)`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// The structured result of [`diff_public_api`], serializable via the existing
+/// `--output-format=json` machinery.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ApiDiff {
+    pub changes: Vec<ApiChange>,
+}
+
+impl ApiDiff {
+    /// Whether the diff contains any breaking change — the CI gate.
+    pub fn has_breaking(&self) -> bool {
+        self.changes.iter().any(|change| change.severity == Severity::Breaking)
+    }
+}
+
+/// The public surface of a package: its public symbols keyed by fully qualified
+/// name, plus the `__all__` export list of each module that declares one.
+#[derive(Default)]
+struct PublicSurface {
+    symbols: BTreeMap<String, SymbolSpec>,
+    exports: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// A public symbol's identity for diffing: its kind and, for functions, the
+/// parameter list whose shape callers depend on.
+#[derive(Clone, PartialEq)]
+struct SymbolSpec {
+    kind: SymbolKind,
+    params: Option<Vec<String>>,
+}
+
+/// Extract and diff the public surfaces of two versions of a package, reporting
+/// every breaking or additive change between `old` and `new`.
+pub fn diff_public_api(
+    old: PackageRoot,
+    old_files: &[PathBuf],
+    new: PackageRoot,
+    new_files: &[PathBuf],
+) -> ApiDiff {
+    let old = public_surface(&old, old_files);
+    let new = public_surface(&new, new_files);
+
+    let mut changes = Vec::new();
+
+    // Symbols present in the old surface: removed, kind-changed, or re-signed.
+    for (qualified, old_spec) in &old.symbols {
+        match new.symbols.get(qualified) {
+            None => changes.push(ApiChange {
+                kind: ChangeKind::Removed,
+                severity: Severity::Breaking,
+                qualified_name: qualified.clone(),
+                detail: None,
+            }),
+            Some(new_spec) if new_spec.kind != old_spec.kind => changes.push(ApiChange {
+                kind: ChangeKind::KindChanged,
+                severity: Severity::Breaking,
+                qualified_name: qualified.clone(),
+                detail: Some(format!("{} -> {}", old_spec.kind, new_spec.kind)),
+            }),
+            Some(new_spec) if new_spec.params != old_spec.params => {
+                // Only functions carry a parameter list; a shift in arity or
+                // parameter names can break positional/keyword callers.
+                if let (Some(old_params), Some(new_params)) = (&old_spec.params, &new_spec.params) {
+                    changes.push(ApiChange {
+                        kind: ChangeKind::SignatureChanged,
+                        severity: Severity::Breaking,
+                        qualified_name: qualified.clone(),
+                        detail: Some(format!(
+                            "({}) -> ({})",
+                            old_params.join(", "),
+                            new_params.join(", ")
+                        )),
+                    });
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    // Symbols only in the new surface are additive.
+    for qualified in new.symbols.keys() {
+        if !old.symbols.contains_key(qualified) {
+            changes.push(ApiChange {
+                kind: ChangeKind::Added,
+                severity: Severity::Additive,
+                qualified_name: qualified.clone(),
+                detail: None,
+            });
+        }
+    }
+
+    // `__all__` entries dropped from a module that still exists are breaking:
+    // they were part of the advertised surface.
+    for (module, old_exports) in &old.exports {
+        let new_exports = new.exports.get(module);
+        for name in old_exports {
+            let still_exported = new_exports.is_some_and(|exports| exports.contains(name));
+            if !still_exported {
+                changes.push(ApiChange {
+                    kind: ChangeKind::AllEntryDropped,
+                    severity: Severity::Breaking,
+                    qualified_name: format!("{module}.{name}"),
+                    detail: None,
+                });
+            }
+        }
+    }
+
+    changes.sort_by(|a, b| a.qualified_name.cmp(&b.qualified_name));
+    ApiDiff { changes }
+}
+
+/// Build the public surface of a package from its files.
+fn public_surface(package: &PackageRoot, files: &[PathBuf]) -> PublicSurface {
+    let mut surface = PublicSurface::default();
+    for file in files {
+        let Ok(source) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        let Ok(parsed) = ruff_python_parser::parse_module(&source) else {
+            continue;
+        };
+        let module = package.module_name(file);
+
+        // Collect candidate symbols and the module's `__all__`, then apply the
+        // export list (or the underscore convention) to keep only public names.
+        let mut candidates: BTreeMap<String, SymbolSpec> = BTreeMap::new();
+        let mut exports: Option<BTreeSet<String>> = None;
+        for stmt in &parsed.syntax().body {
+            match stmt {
+                ast::Stmt::FunctionDef(func) => {
+                    candidates.insert(
+                        func.name.to_string(),
+                        SymbolSpec {
+                            kind: SymbolKind::Function,
+                            params: Some(param_names(&func.parameters)),
+                        },
+                    );
+                }
+                ast::Stmt::ClassDef(class) => {
+                    candidates.insert(
+                        class.name.to_string(),
+                        SymbolSpec { kind: SymbolKind::Class, params: None },
+                    );
+                }
+                ast::Stmt::Assign(assign) => {
+                    if let Some(value) = all_value(assign) {
+                        exports.get_or_insert_with(BTreeSet::new).extend(string_list(value));
+                    } else {
+                        for target in &assign.targets {
+                            if let ast::Expr::Name(name) = target {
+                                candidates.insert(
+                                    name.id.to_string(),
+                                    SymbolSpec { kind: SymbolKind::Variable, params: None },
+                                );
+                            }
+                        }
+                    }
+                }
+                ast::Stmt::AnnAssign(ann) => {
+                    if let ast::Expr::Name(name) = ann.target.as_ref() {
+                        if name.id.as_str() == "__all__" {
+                            if let Some(value) = &ann.value {
+                                exports.get_or_insert_with(BTreeSet::new).extend(string_list(value));
+                            }
+                        } else {
+                            candidates.insert(
+                                name.id.to_string(),
+                                SymbolSpec { kind: SymbolKind::Variable, params: None },
+                            );
+                        }
+                    }
+                }
+                ast::Stmt::TypeAlias(type_alias) => {
+                    if let ast::Expr::Name(name) = type_alias.name.as_ref() {
+                        candidates.insert(
+                            name.id.to_string(),
+                            SymbolSpec { kind: SymbolKind::TypeAlias, params: None },
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (name, spec) in candidates {
+            let public = match &exports {
+                Some(exports) => exports.contains(&name),
+                None => !is_private(&name),
+            };
+            if public {
+                surface.symbols.insert(format!("{module}.{name}"), spec);
+            }
+        }
+        if let Some(exports) = exports {
+            surface.exports.insert(module, exports);
+        }
+    }
+    surface
+}
+
+/// The parameter names of a function, flagging `*args`/`**kwargs` so a change
+/// to them registers as a signature change.
+fn param_names(params: &ast::Parameters) -> Vec<String> {
+    let mut names = Vec::new();
+    for param in &params.posonlyargs {
+        names.push(param.parameter.name.to_string());
+    }
+    for param in &params.args {
+        names.push(param.parameter.name.to_string());
+    }
+    if let Some(vararg) = &params.vararg {
+        names.push(format!("*{}", vararg.name));
+    }
+    for param in &params.kwonlyargs {
+        names.push(param.parameter.name.to_string());
+    }
+    if let Some(kwarg) = &params.kwarg {
+        names.push(format!("**{}", kwarg.name));
+    }
+    names
+}
+
+/// The value assigned to `__all__`, if `assign` targets it.
+fn all_value(assign: &ast::StmtAssign) -> Option<&ast::Expr> {
+    assign
+        .targets
+        .iter()
+        .any(|target| matches!(target, ast::Expr::Name(name) if name.id.as_str() == "__all__"))
+        .then_some(assign.value.as_ref())
+}
+
+/// The string literals of a list/tuple expression.
+fn string_list(expr: &ast::Expr) -> Vec<String> {
+    let elts = match expr {
+        ast::Expr::List(list) => &list.elts,
+        ast::Expr::Tuple(tuple) => &tuple.elts,
+        _ => return Vec::new(),
+    };
+    elts.iter()
+        .filter_map(|elt| match elt {
+            ast::Expr::StringLiteral(string) => Some(string.value.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether `name` is private by the leading-underscore convention.
+fn is_private(name: &str) -> bool {
+    name.starts_with('_') && !name.starts_with("__") && !name.ends_with("__")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a single-module package on disk and return its root and file list.
+    fn package(source: &str) -> (tempfile::TempDir, PackageRoot, Vec<PathBuf>) {
+        crate::test_support::package(&[("core.py", source)])
+    }
+
+    #[test]
+    fn diff_public_api_separates_breaking_from_additive() {
+        let (_old_temp, old, old_files) =
+            package("def foo(a, b):\n    pass\n\n\ndef removed():\n    pass\n");
+        let (_new_temp, new, new_files) =
+            package("def foo(a):\n    pass\n\n\ndef added():\n    pass\n");
+
+        let diff = diff_public_api(old, &old_files, new, &new_files);
+        let changes: Vec<_> = diff
+            .changes
+            .iter()
+            .map(|change| (change.qualified_name.as_str(), change.kind, change.severity))
+            .collect();
+
+        assert_eq!(
+            changes,
+            [
+                ("pkg.core.added", ChangeKind::Added, Severity::Additive),
+                ("pkg.core.foo", ChangeKind::SignatureChanged, Severity::Breaking),
+                ("pkg.core.removed", ChangeKind::Removed, Severity::Breaking),
+            ]
+        );
+        assert!(diff.has_breaking());
+    }
+
+    #[test]
+    fn diff_public_api_is_empty_for_identical_surfaces() {
+        let source = "def foo(a):\n    pass\n";
+        let (_old_temp, old, old_files) = package(source);
+        let (_new_temp, new, new_files) = package(source);
+
+        let diff = diff_public_api(old, &old_files, new, &new_files);
+
+        assert!(diff.changes.is_empty());
+        assert!(!diff.has_breaking());
+    }
+}
@@ -0,0 +1,242 @@
+//! Binding analysis for Python source: given a file and the package it belongs
+//! to, determine the module's top-level bindings (its imports and its local
+//! definitions) and how often each one is used within the file.
+//!
+//! The [`api`] module builds on this per-file view to aggregate a package's
+//! public surface across every file that references it.
+
+use std::path::Path;
+
+use ruff_python_ast as ast;
+use ruff_python_ast::ExprContext;
+
+pub mod api;
+pub mod diff;
+pub mod package;
+pub mod strict;
+#[cfg(test)]
+pub(crate) mod test_support;
+
+use package::PackageRoot;
+
+/// How a top-level name came to be bound in a module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopLevelBindingType {
+    /// Brought into scope by an `import` / `from ... import ...` statement.
+    Imported,
+    /// Defined in the module itself (a function, class, or assignment).
+    LocallyDefined,
+}
+
+/// A single name bound at a module's top level, together with how it was bound
+/// and how many times it is referenced within the same file.
+#[derive(Debug, Clone)]
+pub struct TopLevelBinding {
+    /// The bound name as it appears in the module namespace (the alias, for
+    /// aliased imports).
+    pub name: String,
+    /// Whether the name is imported or locally defined.
+    pub binding_type: TopLevelBindingType,
+    /// The dotted name the binding ultimately refers to: the fully qualified
+    /// name of a local definition, or the imported target of an import. `None`
+    /// when no meaningful qualified name applies.
+    pub qualified_name: Option<String>,
+    /// Number of `Load`-context references to the binding elsewhere in the file.
+    pub usage_count: usize,
+}
+
+/// Analyze `source` (the contents of `path`, a file rooted in `package`) and
+/// return its top-level bindings with per-file usage counts.
+///
+/// Unparsable sources yield an empty binding list rather than an error, so a
+/// single malformed file never aborts a package-wide walk.
+pub fn analyze_source_code(path: &Path, package: PackageRoot, source: &str) -> Vec<TopLevelBinding> {
+    let Ok(parsed) = ruff_python_parser::parse_module(source) else {
+        return Vec::new();
+    };
+
+    let module_name = package.module_name(path);
+    let current_package = package_of(path, &module_name);
+
+    // First collect the bindings in source order, then tally references in a
+    // single pass over the whole module.
+    let mut bindings = Vec::new();
+    for stmt in &parsed.syntax().body {
+        collect_bindings(stmt, &module_name, &current_package, &mut bindings);
+    }
+
+    let mut counter = NameUsage::default();
+    for stmt in &parsed.syntax().body {
+        counter.visit_stmt(stmt);
+    }
+    for binding in &mut bindings {
+        binding.usage_count = counter.count(&binding.name);
+    }
+
+    bindings
+}
+
+/// Record the top-level binding(s) a single statement introduces.
+fn collect_bindings(
+    stmt: &ast::Stmt,
+    module_name: &str,
+    current_package: &str,
+    bindings: &mut Vec<TopLevelBinding>,
+) {
+    match stmt {
+        ast::Stmt::Import(import) => {
+            for alias in &import.names {
+                let target = alias.name.to_string();
+                // `import a.b.c` binds `a`; `import a.b.c as x` binds `x`.
+                let name = alias
+                    .asname
+                    .as_ref()
+                    .map_or_else(|| first_component(&target).to_string(), ToString::to_string);
+                bindings.push(TopLevelBinding {
+                    name,
+                    binding_type: TopLevelBindingType::Imported,
+                    qualified_name: Some(target),
+                    usage_count: 0,
+                });
+            }
+        }
+        ast::Stmt::ImportFrom(import) => {
+            // Resolve the dotted prefix a relative import (`from .core import x`,
+            // `from ..pkg import y`) names, anchored at the current package.
+            let module = resolve_import_module(
+                import.module.as_ref().map(ast::Identifier::as_str),
+                import.level,
+                current_package,
+            );
+            for alias in &import.names {
+                let imported = alias.name.to_string();
+                let name = alias.asname.as_ref().map_or_else(|| imported.clone(), ToString::to_string);
+                let qualified = module
+                    .as_ref()
+                    .map(|m| format!("{m}.{imported}"))
+                    .or(Some(imported));
+                bindings.push(TopLevelBinding {
+                    name,
+                    binding_type: TopLevelBindingType::Imported,
+                    qualified_name: qualified,
+                    usage_count: 0,
+                });
+            }
+        }
+        ast::Stmt::FunctionDef(func) => bindings.push(local(func.name.as_str(), module_name)),
+        ast::Stmt::ClassDef(class) => bindings.push(local(class.name.as_str(), module_name)),
+        ast::Stmt::Assign(assign) => {
+            for target in &assign.targets {
+                if let ast::Expr::Name(name) = target {
+                    bindings.push(local(name.id.as_str(), module_name));
+                }
+            }
+        }
+        ast::Stmt::AnnAssign(ann) => {
+            if let ast::Expr::Name(name) = ann.target.as_ref() {
+                bindings.push(local(name.id.as_str(), module_name));
+            }
+        }
+        ast::Stmt::TypeAlias(type_alias) => {
+            if let ast::Expr::Name(name) = type_alias.name.as_ref() {
+                bindings.push(local(name.id.as_str(), module_name));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Build a locally-defined binding with its fully qualified name.
+fn local(name: &str, module_name: &str) -> TopLevelBinding {
+    TopLevelBinding {
+        name: name.to_string(),
+        binding_type: TopLevelBindingType::LocallyDefined,
+        qualified_name: Some(format!("{module_name}.{name}")),
+        usage_count: 0,
+    }
+}
+
+/// The first dotted component of `name` (`a.b.c` -> `a`).
+fn first_component(name: &str) -> &str {
+    name.split_once('.').map_or(name, |(head, _)| head)
+}
+
+/// The dotted package a file belongs to: the module name itself for an
+/// `__init__.py` (which *is* its package), otherwise the module name with its
+/// final component stripped.
+pub(crate) fn package_of(path: &Path, module_name: &str) -> String {
+    let is_init = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name == "__init__.py");
+    if is_init {
+        module_name.to_string()
+    } else {
+        module_name
+            .rsplit_once('.')
+            .map_or_else(String::new, |(head, _)| head.to_string())
+    }
+}
+
+/// Resolve the dotted module an `import` statement targets. Absolute imports
+/// (`level == 0`) keep their explicit module; relative imports walk up
+/// `level - 1` packages from `current_package` and append the explicit module,
+/// mirroring Python's relative-import semantics.
+pub(crate) fn resolve_import_module(
+    module: Option<&str>,
+    level: u32,
+    current_package: &str,
+) -> Option<String> {
+    if level == 0 {
+        return module.map(ToString::to_string);
+    }
+
+    let mut components: Vec<&str> = if current_package.is_empty() {
+        Vec::new()
+    } else {
+        current_package.split('.').collect()
+    };
+    for _ in 0..level.saturating_sub(1) {
+        components.pop();
+    }
+
+    let mut base = components.join(".");
+    if let Some(module) = module {
+        if base.is_empty() {
+            base = module.to_string();
+        } else {
+            base.push('.');
+            base.push_str(module);
+        }
+    }
+    (!base.is_empty()).then_some(base)
+}
+
+/// Counts `Load`-context [`ast::Expr::Name`] references per identifier across a
+/// module body. Import and definition statements bind names rather than reading
+/// them, so they are skipped.
+#[derive(Default)]
+struct NameUsage {
+    counts: std::collections::HashMap<String, usize>,
+}
+
+impl NameUsage {
+    fn count(&self, name: &str) -> usize {
+        self.counts.get(name).copied().unwrap_or(0)
+    }
+
+    fn visit_stmt(&mut self, stmt: &ast::Stmt) {
+        ast::visitor::source_order::walk_stmt(self, stmt);
+    }
+}
+
+impl ast::visitor::source_order::SourceOrderVisitor<'_> for NameUsage {
+    fn visit_expr(&mut self, expr: &ast::Expr) {
+        if let ast::Expr::Name(name) = expr {
+            if name.ctx == ExprContext::Load {
+                *self.counts.entry(name.id.to_string()).or_default() += 1;
+            }
+        }
+        ast::visitor::source_order::walk_expr(self, expr);
+    }
+}
@@ -0,0 +1,72 @@
+//! The package model used by the binding analysis: where a package is rooted on
+//! disk and how an individual file maps to a dotted module name.
+
+use std::path::{Path, PathBuf};
+
+/// The root directory of the package (or project) under analysis.
+///
+/// Module names are computed relative to this root, so a file at
+/// `<root>/mypkg/core.py` becomes `mypkg.core` and its `__init__.py` becomes
+/// `mypkg`.
+#[derive(Debug, Clone)]
+pub struct PackageRoot {
+    root: PathBuf,
+}
+
+impl PackageRoot {
+    /// Build a package rooted at `path`.
+    pub fn root(path: &Path) -> Self {
+        Self {
+            root: path.to_path_buf(),
+        }
+    }
+
+    /// The root directory.
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// The dotted module name of `file`, relative to the package root. Files
+    /// outside the root fall back to their bare stem, and `__init__.py` names
+    /// the package directory that contains it.
+    pub fn module_name(&self, file: &Path) -> String {
+        let Ok(relative) = file.strip_prefix(&self.root) else {
+            return file
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("unknown")
+                .to_string();
+        };
+
+        let mut components: Vec<String> = relative
+            .components()
+            .filter_map(|component| component.as_os_str().to_str().map(str::to_string))
+            .collect();
+
+        // Replace the trailing file component with its stem, dropping it
+        // entirely for `__init__.py` so the package directory names itself.
+        if let Some(last) = components.pop() {
+            let stem = Path::new(&last)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or(&last);
+            if stem != "__init__" {
+                components.push(stem.to_string());
+            }
+        }
+
+        if components.is_empty() {
+            return file
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("unknown")
+                .to_string();
+        }
+        components.join(".")
+    }
+
+    /// The fully qualified name of `symbol` defined in `file`.
+    pub fn qualified_name(&self, file: &Path, symbol: &str) -> String {
+        format!("{}.{}", self.module_name(file), symbol)
+    }
+}
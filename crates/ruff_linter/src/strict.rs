@@ -0,0 +1,351 @@
+//! Strict boundary enforcement.
+//!
+//! After [`analyze_package_api`](crate::api::analyze_package_api) establishes
+//! what a package defines, strict mode goes the other way: it inspects what a
+//! referencing file *reaches for* and flags every access that crosses into a
+//! name the target module does not expose — a name that does not exist, is
+//! private by convention (leading underscore), or is excluded from a declared
+//! `__all__`. This turns pubscan into a boundary linter that catches code
+//! depending on internals before a refactor breaks it.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use ruff_python_ast as ast;
+use ruff_python_ast::ExprContext;
+use ruff_text_size::Ranged;
+
+use crate::package::PackageRoot;
+
+/// Why a cross-module access was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// The name is private by convention or excluded from the module's `__all__`.
+    Private,
+    /// The name does not exist on the target module at all.
+    Undefined,
+}
+
+/// A single strict-mode violation: a referencing file reaching a name the
+/// target module does not expose.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    /// The file containing the offending access.
+    pub file: PathBuf,
+    /// The 1-based line of the access.
+    pub line: usize,
+    /// The fully qualified name that was accessed (`mypackage.core._helper`).
+    pub accessed: String,
+    /// Why it was rejected.
+    pub kind: DiagnosticKind,
+}
+
+/// The public surface of a single module.
+struct ModuleSurface {
+    /// Every top-level name the module defines.
+    defined: HashSet<String>,
+    /// The names listed in `__all__`, if the module declares one.
+    exported: Option<HashSet<String>>,
+}
+
+impl ModuleSurface {
+    /// Classify `name` against the module's surface: `None` if it is public,
+    /// otherwise the reason it is off-limits.
+    fn reject(&self, name: &str) -> Option<DiagnosticKind> {
+        if let Some(exported) = &self.exported {
+            if exported.contains(name) {
+                None
+            } else if self.defined.contains(name) {
+                Some(DiagnosticKind::Private)
+            } else {
+                Some(DiagnosticKind::Undefined)
+            }
+        } else if !self.defined.contains(name) {
+            Some(DiagnosticKind::Undefined)
+        } else if is_private(name) {
+            Some(DiagnosticKind::Private)
+        } else {
+            None
+        }
+    }
+}
+
+/// Analyze `files` as a package and report every access that reaches a
+/// non-public name on one of the package's own modules.
+pub fn analyze_package_strict(package: PackageRoot, files: &[PathBuf]) -> Vec<Diagnostic> {
+    // Build each module's public surface first.
+    let mut modules: HashMap<String, ModuleSurface> = HashMap::new();
+    let mut sources: Vec<(PathBuf, String)> = Vec::with_capacity(files.len());
+    for file in files {
+        let Ok(source) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        if let Some(surface) = module_surface(&source) {
+            modules.insert(package.module_name(file), surface);
+        }
+        sources.push((file.clone(), source));
+    }
+
+    // Then check every access against the surface of the module it names.
+    let mut diagnostics = Vec::new();
+    for (file, source) in &sources {
+        let current_package = crate::package_of(file, &package.module_name(file));
+        for (accessed, offset) in module_accesses(source, &current_package) {
+            // A plain submodule access (`from pkg import sub`, `pkg.sub.x`) is
+            // always legitimate, even though `sub` is not a top-level name on
+            // `pkg`.
+            if modules.contains_key(&accessed) {
+                continue;
+            }
+            let Some((module, attr)) = split_known_module(&accessed, &modules) else {
+                continue;
+            };
+            if let Some(kind) = modules[module].reject(attr) {
+                diagnostics.push(Diagnostic {
+                    file: file.clone(),
+                    line: line_of(source, offset),
+                    accessed: format!("{module}.{attr}"),
+                    kind,
+                });
+            }
+        }
+    }
+
+    // A single access can surface more than once (a dotted chain is walked at
+    // each of its attribute nodes); collapse exact duplicates.
+    diagnostics.sort_by(|a, b| {
+        (a.file.as_path(), a.line, a.accessed.as_str())
+            .cmp(&(b.file.as_path(), b.line, b.accessed.as_str()))
+    });
+    // `kind` is a pure function of `accessed`, so the sort key above already
+    // groups identical diagnostics adjacently.
+    diagnostics.dedup_by(|a, b| a.file == b.file && a.line == b.line && a.accessed == b.accessed);
+    diagnostics
+}
+
+/// Extract the defined names and `__all__` export list of a module.
+fn module_surface(source: &str) -> Option<ModuleSurface> {
+    let parsed = ruff_python_parser::parse_module(source).ok()?;
+
+    let mut defined = HashSet::new();
+    let mut exported: Option<HashSet<String>> = None;
+    for stmt in &parsed.syntax().body {
+        match stmt {
+            ast::Stmt::FunctionDef(func) => {
+                defined.insert(func.name.to_string());
+            }
+            ast::Stmt::ClassDef(class) => {
+                defined.insert(class.name.to_string());
+            }
+            // Imported and re-exported names are part of the module's surface:
+            // `module.name` resolves to them just like a local definition.
+            ast::Stmt::Import(import) => {
+                for alias in &import.names {
+                    let name = alias.asname.as_ref().map_or_else(
+                        || alias.name.split('.').next().unwrap_or(&alias.name).to_string(),
+                        ToString::to_string,
+                    );
+                    defined.insert(name);
+                }
+            }
+            ast::Stmt::ImportFrom(import) => {
+                for alias in &import.names {
+                    let name = alias
+                        .asname
+                        .as_ref()
+                        .map_or_else(|| alias.name.to_string(), ToString::to_string);
+                    defined.insert(name);
+                }
+            }
+            ast::Stmt::Assign(assign) => {
+                if assign_targets_all(assign) {
+                    exported.get_or_insert_with(HashSet::new).extend(string_list(&assign.value));
+                } else {
+                    for target in &assign.targets {
+                        if let ast::Expr::Name(name) = target {
+                            defined.insert(name.id.to_string());
+                        }
+                    }
+                }
+            }
+            ast::Stmt::AnnAssign(ann) => {
+                if let ast::Expr::Name(name) = ann.target.as_ref() {
+                    defined.insert(name.id.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Some(ModuleSurface { defined, exported })
+}
+
+/// Whether an assignment targets `__all__`.
+fn assign_targets_all(assign: &ast::StmtAssign) -> bool {
+    assign
+        .targets
+        .iter()
+        .any(|target| matches!(target, ast::Expr::Name(name) if name.id.as_str() == "__all__"))
+}
+
+/// The string literals of a list/tuple expression, ignoring non-string elements.
+fn string_list(expr: &ast::Expr) -> Vec<String> {
+    let elts = match expr {
+        ast::Expr::List(list) => &list.elts,
+        ast::Expr::Tuple(tuple) => &tuple.elts,
+        _ => return Vec::new(),
+    };
+    elts.iter()
+        .filter_map(|elt| match elt {
+            ast::Expr::StringLiteral(string) => Some(string.value.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether `name` is private by the leading-underscore convention (but not a
+/// dunder like `__init__`).
+fn is_private(name: &str) -> bool {
+    name.starts_with('_') && !name.starts_with("__") && !name.ends_with("__")
+}
+
+/// Split `accessed` into the longest known-module prefix and the attribute that
+/// follows it, e.g. `mypackage.config.DEFAULT_CONFIG.copy` with module
+/// `mypackage.config` yields `("mypackage.config", "DEFAULT_CONFIG")`.
+fn split_known_module<'a>(
+    accessed: &'a str,
+    modules: &HashMap<String, ModuleSurface>,
+) -> Option<(&'a str, &'a str)> {
+    let mut best: Option<(&str, &str)> = None;
+    for (index, _) in accessed.match_indices('.') {
+        let module = &accessed[..index];
+        let rest = &accessed[index + 1..];
+        let attr = rest.split_once('.').map_or(rest, |(head, _)| head);
+        if modules.contains_key(module) {
+            best = Some((module, attr));
+        }
+    }
+    best
+}
+
+/// The 1-based line number of the byte `offset` in `source`.
+fn line_of(source: &str, offset: usize) -> usize {
+    source[..offset.min(source.len())].bytes().filter(|&b| b == b'\n').count() + 1
+}
+
+/// Collect every cross-module access in `source` as `(dotted_name, byte_offset)`
+/// pairs: `from pkg.mod import attr` targets and `pkg.mod.attr` attribute
+/// chains.
+fn module_accesses(source: &str, current_package: &str) -> Vec<(String, usize)> {
+    let Ok(parsed) = ruff_python_parser::parse_module(source) else {
+        return Vec::new();
+    };
+
+    let mut collector = AccessCollector { accesses: Vec::new() };
+    for stmt in &parsed.syntax().body {
+        if let ast::Stmt::ImportFrom(import) = stmt {
+            if let Some(module) = crate::resolve_import_module(
+                import.module.as_ref().map(ast::Identifier::as_str),
+                import.level,
+                current_package,
+            ) {
+                for alias in &import.names {
+                    // `from pkg import *` binds nothing nameable to check.
+                    if alias.name.as_str() == "*" {
+                        continue;
+                    }
+                    let offset = alias.range().start().to_usize();
+                    collector.accesses.push((format!("{module}.{}", alias.name), offset));
+                }
+            }
+        }
+        collector.visit_stmt(stmt);
+    }
+    collector.accesses
+}
+
+/// Walks a module body collecting `pkg.mod.attr` attribute chains.
+struct AccessCollector {
+    accesses: Vec<(String, usize)>,
+}
+
+impl AccessCollector {
+    fn visit_stmt(&mut self, stmt: &ast::Stmt) {
+        ast::visitor::source_order::walk_stmt(self, stmt);
+    }
+}
+
+impl ast::visitor::source_order::SourceOrderVisitor<'_> for AccessCollector {
+    fn visit_expr(&mut self, expr: &ast::Expr) {
+        if let ast::Expr::Attribute(attr) = expr {
+            if attr.ctx == ExprContext::Load {
+                if let Some(path) = attribute_path(expr) {
+                    self.accesses.push((path, expr.range().start().to_usize()));
+                }
+            }
+        }
+        ast::visitor::source_order::walk_expr(self, expr);
+    }
+}
+
+/// The dotted name of an attribute chain rooted at a bare name, or `None`.
+fn attribute_path(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::Name(name) => Some(name.id.to_string()),
+        ast::Expr::Attribute(attr) => {
+            let base = attribute_path(&attr.value)?;
+            Some(format!("{base}.{}", attr.attr))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::package;
+
+    use super::*;
+
+    #[test]
+    fn analyze_package_strict_flags_private_and_undefined_access() {
+        let (_temp, package, files) = package(&[
+            ("core.py", "def add():\n    pass\n\n\ndef _secret():\n    pass\n"),
+            ("app.py", "from .core import add, _secret, missing\n"),
+        ]);
+
+        let diagnostics = analyze_package_strict(package, &files);
+        let reported: Vec<_> = diagnostics
+            .iter()
+            .map(|diag| (diag.accessed.as_str(), diag.kind))
+            .collect();
+
+        // `add` is public; `_secret` is private by convention; `missing` does
+        // not exist on the module at all.
+        assert_eq!(
+            reported,
+            [
+                ("pkg.core._secret", DiagnosticKind::Private),
+                ("pkg.core.missing", DiagnosticKind::Undefined),
+            ]
+        );
+    }
+
+    #[test]
+    fn analyze_package_strict_honors_all_export_list() {
+        let (_temp, package, files) = package(&[
+            (
+                "core.py",
+                "__all__ = [\"add\"]\n\n\ndef add():\n    pass\n\n\ndef helper():\n    pass\n",
+            ),
+            ("app.py", "from .core import add, helper\n"),
+        ]);
+
+        let diagnostics = analyze_package_strict(package, &files);
+
+        // `helper` is defined but excluded from `__all__`, so reaching it is a
+        // private-access violation even though it has no leading underscore.
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].accessed, "pkg.core.helper");
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::Private);
+    }
+}
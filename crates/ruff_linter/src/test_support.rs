@@ -0,0 +1,29 @@
+//! Shared fixture helpers for this crate's `#[cfg(test)]` modules.
+//!
+//! [`api`](crate::api), [`strict`](crate::strict), and [`diff`](crate::diff)
+//! each need to write a small on-disk package and hand back its root plus file
+//! list; this lives in one place so the three test modules don't each
+//! maintain their own copy.
+
+use std::fs;
+use std::path::PathBuf;
+
+use tempfile::tempdir;
+
+use crate::package::PackageRoot;
+
+/// Build a package on disk from `(relative_path, source)` pairs and return its
+/// root plus the sorted list of files.
+pub(crate) fn package(files: &[(&str, &str)]) -> (tempfile::TempDir, PackageRoot, Vec<PathBuf>) {
+    let temp = tempdir().expect("temp dir");
+    let root = temp.path().join("pkg");
+    let mut paths = Vec::new();
+    for (name, source) in files {
+        let path = root.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, source).unwrap();
+        paths.push(path);
+    }
+    paths.sort();
+    (temp, PackageRoot::root(temp.path()), paths)
+}